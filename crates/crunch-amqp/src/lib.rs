@@ -0,0 +1,366 @@
+use std::{
+    collections::HashSet,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::TransportError, EventInfo, Transport};
+use futures::{Stream, StreamExt};
+use lapin::{
+    acker::Acker,
+    options::{
+        BasicAckOptions, BasicConsumeOptions, BasicPublishOptions, BasicQosOptions,
+        ConfirmSelectOptions, ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions,
+    },
+    types::FieldTable,
+    BasicProperties, Channel, Connection, ConnectionProperties, Consumer, ExchangeKind,
+};
+
+/// Marks messages as persistent, such that durable queues keep them across broker restarts
+const PERSISTENT: u8 = 2;
+/// Wait before subscribing again after the channel failed, doubled for every failure after it up to `MAX_BACKOFF`
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct AmqpTransportOptions {
+    /// Name of the subscribing service, which gets a durable queue per event. Instances of a service share the queues,
+    /// and as such each event is handled once per service
+    pub service: String,
+    /// How many unacknowledged events a subscription holds at once
+    pub prefetch: u16,
+}
+
+impl AmqpTransportOptions {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            prefetch: 100,
+        }
+    }
+}
+
+/// A transport on an AMQP 0.9.1 broker such as RabbitMQ. Each domain has a topic exchange, to which events are
+/// published with `entity.event` as routing key.
+///
+/// Publishing waits for the broker to confirm the event, and subscriptions acknowledge an event once the next one is
+/// asked for. A lost connection or channel is opened again when it is next used, and subscriptions subscribe again
+/// with backoff
+#[derive(Clone)]
+pub struct AmqpTransport {
+    uri: String,
+    connection: Arc<tokio::sync::Mutex<Option<Arc<Connection>>>>,
+    publish_channel: Arc<tokio::sync::Mutex<Option<Channel>>>,
+    /// Exchanges declared by this transport, such that publishing only declares them once
+    declared: Arc<Mutex<HashSet<String>>>,
+    options: AmqpTransportOptions,
+}
+
+impl AmqpTransport {
+    pub async fn new(uri: &str, options: AmqpTransportOptions) -> Result<Self, TransportError> {
+        let transport = Self {
+            uri: uri.to_string(),
+            connection: Arc::default(),
+            publish_channel: Arc::default(),
+            declared: Arc::default(),
+            options,
+        };
+        transport
+            .publish_channel()
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to connect to amqp broker: {}", e))
+            .map_err(TransportError::Err)?;
+
+        Ok(transport)
+    }
+
+    /// The connection to the broker, connecting again if it was lost
+    async fn connection(&self) -> anyhow::Result<Arc<Connection>> {
+        let mut connection = self.connection.lock().await;
+        if let Some(connection) = connection.as_ref().filter(|c| c.status().connected()) {
+            return Ok(connection.clone());
+        }
+
+        tracing::debug!("connecting to amqp broker");
+        let connected =
+            Arc::new(Connection::connect(&self.uri, ConnectionProperties::default()).await?);
+        *connection = Some(connected.clone());
+
+        Ok(connected)
+    }
+
+    /// The channel events are published on, opened again if it was closed
+    async fn publish_channel(&self) -> anyhow::Result<Channel> {
+        let mut channel = self.publish_channel.lock().await;
+        if let Some(channel) = channel.as_ref().filter(|c| c.status().connected()) {
+            return Ok(channel.clone());
+        }
+
+        let opened = self.connection().await?.create_channel().await?;
+        opened
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+        *channel = Some(opened.clone());
+
+        Ok(opened)
+    }
+
+    async fn declare_exchange(&self, channel: &Channel, exchange: &str) -> anyhow::Result<()> {
+        channel
+            .exchange_declare(
+                exchange,
+                ExchangeKind::Topic,
+                ExchangeDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn publish_event(&self, event_info: &EventInfo, content: Vec<u8>) -> anyhow::Result<()> {
+        let channel = self.publish_channel().await?;
+        let exchange = event_info.exchange_name();
+        let declared = self.declared.lock().unwrap().contains(&exchange);
+        if !declared {
+            self.declare_exchange(&channel, &exchange).await?;
+            self.declared.lock().unwrap().insert(exchange.clone());
+        }
+
+        let confirmation = channel
+            .basic_publish(
+                &exchange,
+                &event_info.routing_key(),
+                BasicPublishOptions::default(),
+                &content,
+                BasicProperties::default().with_delivery_mode(PERSISTENT),
+            )
+            .await?
+            .await?;
+        if confirmation.is_nack() {
+            anyhow::bail!(
+                "broker rejected event: {}.{}",
+                exchange,
+                event_info.routing_key()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn subscribe(&self, event_info: &EventInfo) -> anyhow::Result<Subscription> {
+        // Prefetch applies per channel, so each subscription has its own
+        let channel = self.connection().await?.create_channel().await?;
+        let exchange = event_info.exchange_name();
+        let queue = event_info.queue_name(&self.options.service);
+
+        self.declare_exchange(&channel, &exchange).await?;
+        channel
+            .queue_declare(
+                &queue,
+                QueueDeclareOptions {
+                    durable: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+        channel
+            .queue_bind(
+                &queue,
+                &exchange,
+                &event_info.routing_key(),
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+        channel
+            .basic_qos(self.options.prefetch, BasicQosOptions::default())
+            .await?;
+
+        let consumer = channel
+            .basic_consume(
+                &queue,
+                &uuid::Uuid::new_v4().to_string(),
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        Ok(Subscription {
+            _channel: channel,
+            consumer,
+            unacked: None,
+        })
+    }
+}
+
+struct Subscription {
+    // Closing the channel hands the unacknowledged events back to the queue
+    _channel: Channel,
+    consumer: Consumer,
+    /// The event handed out last, it is acknowledged when the next one is asked for
+    unacked: Option<Acker>,
+}
+
+impl Subscription {
+    /// The next event. Closing the channel or connection cancels the consumer, which is an error as well
+    async fn next(&mut self) -> anyhow::Result<Vec<u8>> {
+        if let Some(acker) = self.unacked.take() {
+            acker.ack(BasicAckOptions::default()).await?;
+        }
+
+        match self.consumer.next().await {
+            Some(delivery) => {
+                let delivery = delivery?;
+                self.unacked = Some(delivery.acker);
+                Ok(delivery.data)
+            }
+            None => anyhow::bail!("amqp consumer was cancelled"),
+        }
+    }
+}
+
+/// Subscribes again once the subscription failed, as its channel or connection was lost or the broker cancelled it
+struct Subscriber {
+    transport: AmqpTransport,
+    event_info: EventInfo,
+    /// None after the subscription failed, until it is subscribed again
+    subscription: Option<Subscription>,
+}
+
+impl Subscriber {
+    async fn next(&mut self) -> anyhow::Result<Vec<u8>> {
+        let subscription = match self.subscription.take() {
+            Some(subscription) => subscription,
+            None => {
+                let subscription = self.transport.subscribe(&self.event_info).await?;
+                tracing::info!("subscribed to amqp again");
+                subscription
+            }
+        };
+
+        let next = self.subscription.insert(subscription).next().await;
+        if next.is_err() {
+            // Dropping the channel hands its unacknowledged events back to the queue
+            self.subscription = None;
+        }
+
+        next
+    }
+}
+
+#[async_trait]
+impl Transport for AmqpTransport {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        self.publish_event(event_info, content)
+            .await
+            .map_err(TransportError::Err)
+    }
+
+    async fn subscriber(
+        &self,
+        event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        let subscription = self
+            .subscribe(event_info)
+            .await
+            .map_err(TransportError::Err)?;
+
+        let subscriber = Subscriber {
+            transport: self.clone(),
+            event_info: event_info.clone(),
+            subscription: Some(subscription),
+        };
+
+        let stream = futures::stream::unfold(subscriber, |mut subscriber| async move {
+            let mut backoff = INITIAL_BACKOFF;
+            loop {
+                match subscriber.next().await {
+                    Ok(content) => {
+                        tracing::trace!("got event from amqp");
+                        return Some((content, subscriber));
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            "failed to receive event from amqp, subscribing again in {backoff:?}: {e}"
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Box::pin(stream)))
+    }
+}
+
+trait EventInfoExt {
+    fn exchange_name(&self) -> String;
+    fn routing_key(&self) -> String;
+    fn queue_name(&self, service: &str) -> String;
+}
+
+impl EventInfoExt for EventInfo {
+    fn exchange_name(&self) -> String {
+        format!("crunch.{}", self.domain)
+    }
+
+    fn routing_key(&self) -> String {
+        format!("{}.{}", self.entity_type, self.event_name)
+    }
+
+    fn queue_name(&self, service: &str) -> String {
+        format!(
+            "{}.crunch.{}.{}.{}",
+            service, self.domain, self.entity_type, self.event_name
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crunch_traits::testing::{broker_url, event_info, next_event};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transport_recovers_from_closed_connection() -> anyhow::Result<()> {
+        let Some(uri) = broker_url("AMQP_URL") else {
+            return Ok(());
+        };
+        let transport =
+            AmqpTransport::new(&uri, AmqpTransportOptions::new("test-recovers")).await?;
+        let event_info = event_info();
+
+        let mut stream = transport.subscriber(&event_info).await?.unwrap();
+        transport
+            .connection()
+            .await?
+            .close(0, "closed by test")
+            .await?;
+
+        // Both the publish channel and the subscription are opened again on a new connection
+        transport
+            .publish(&event_info, b"after-close".to_vec())
+            .await?;
+        assert_eq!(
+            b"after-close".to_vec(),
+            next_event(&mut stream, Duration::from_secs(10)).await?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use crunch_amqp::{AmqpTransport, AmqpTransportOptions};
+use crunch_traits::{
+    testing::{broker_url, event_info, next_event, publish_subscribe},
+    Transport,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn transport(uri: &str, service: &str) -> anyhow::Result<AmqpTransport> {
+    Ok(AmqpTransport::new(uri, AmqpTransportOptions::new(service)).await?)
+}
+
+#[tokio::test]
+async fn test_transport_publish_subscribe() -> anyhow::Result<()> {
+    let Some(uri) = broker_url("AMQP_URL") else {
+        return Ok(());
+    };
+
+    publish_subscribe(&transport(&uri, "test-publish-subscribe").await?, TIMEOUT).await
+}
+
+#[tokio::test]
+async fn test_transport_durable_queue_keeps_events() -> anyhow::Result<()> {
+    let Some(uri) = broker_url("AMQP_URL") else {
+        return Ok(());
+    };
+    let transport = transport(&uri, "test-durable").await?;
+    let event_info = event_info();
+
+    // Subscribing declares the queue of the service
+    drop(transport.subscriber(&event_info).await?.unwrap());
+    transport
+        .publish(&event_info, b"while-away".to_vec())
+        .await?;
+
+    let mut stream = transport.subscriber(&event_info).await?.unwrap();
+    assert_eq!(
+        b"while-away".to_vec(),
+        next_event(&mut stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transport_services_each_receive_events() -> anyhow::Result<()> {
+    let Some(uri) = broker_url("AMQP_URL") else {
+        return Ok(());
+    };
+    let first = transport(&uri, "test-services-first").await?;
+    let second = transport(&uri, "test-services-second").await?;
+    let event_info = event_info();
+
+    let mut first_stream = first.subscriber(&event_info).await?.unwrap();
+    let mut second_stream = second.subscriber(&event_info).await?.unwrap();
+    first.publish(&event_info, b"event".to_vec()).await?;
+
+    assert_eq!(
+        b"event".to_vec(),
+        next_event(&mut first_stream, TIMEOUT).await?
+    );
+    assert_eq!(
+        b"event".to_vec(),
+        next_event(&mut second_stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{collections::HashMap, path::Path};
+
+use clap::ValueEnum;
+use crunch_codegen::{SchemaDescription, Target};
+use crunch_file::Config;
+
+use crate::workspace::Member;
+
+#[derive(Clone, ValueEnum)]
+pub enum DocsFormat {
+    Yaml,
+    Json,
+}
+
+impl DocsFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocsFormat::Yaml => "yaml",
+            DocsFormat::Json => "json",
+        }
+    }
+}
+
+pub async fn asyncapi(
+    crunch_dir: &Path,
+    config: &Config,
+    nats_url: &str,
+    format: &DocsFormat,
+) -> anyhow::Result<String> {
+    let mut descriptions = Vec::new();
+    for publish in config.publish.iter().flatten() {
+        descriptions.push(
+            describe(
+                crunch_dir,
+                &Target::from_publish(publish, &config.service.domain),
+            )
+            .await?,
+        );
+    }
+
+    let document = crunch_codegen::asyncapi(&config.service, nats_url, &descriptions);
+
+    Ok(match format {
+        DocsFormat::Yaml => serde_yaml::to_string(&document)?,
+        DocsFormat::Json => serde_json::to_string_pretty(&document)?,
+    })
+}
+
+async fn describe(crunch_dir: &Path, target: &Target) -> anyhow::Result<SchemaDescription> {
+    target
+        .codegen()
+        .describe(&crunch_dir.join(&target.schema_path))
+        .await
+}
+
+/// Writes to `output`, or stdout if unset
+pub async fn write_output(output: Option<&Path>, content: &str) -> anyhow::Result<()> {
+    match output {
+        Some(output) => {
+            if let Some(dir) = output.parent() {
+                if !dir.as_os_str().is_empty() {
+                    tokio::fs::create_dir_all(dir).await?;
+                }
+            }
+            tokio::fs::write(output, content).await?;
+            println!("success: wrote {}", output.display());
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, ValueEnum)]
+pub enum CatalogFormat {
+    Markdown,
+    Html,
+}
+
+/// Renders the events of every member, subscriptions between them make up the consumers
+pub async fn catalog(
+    members: &[Member],
+    output: &Path,
+    format: &CatalogFormat,
+) -> anyhow::Result<()> {
+    let mut consumers: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for member in members {
+        for subscription in member.config.subscription.iter().flatten() {
+            consumers
+                .entry((subscription.domain.clone(), subscription.service.clone()))
+                .or_default()
+                .push(member.config.service.service.clone());
+        }
+    }
+
+    let mut events = Vec::new();
+    for Member {
+        crunch_dir, config, ..
+    } in members
+    {
+        let service_consumers = consumers
+            .get(&(
+                config.service.domain.clone(),
+                config.service.service.clone(),
+            ))
+            .cloned()
+            .unwrap_or_default();
+
+        for publish in config.publish.iter().flatten() {
+            let target = Target::from_publish(publish, &config.service.domain);
+            let description = describe(crunch_dir, &target).await?;
+            events.extend(crunch_codegen::catalog_events(
+                &description,
+                &config.service.service,
+                &service_consumers,
+            ));
+        }
+    }
+
+    let format = match format {
+        CatalogFormat::Markdown => crunch_codegen::CatalogFormat::Markdown,
+        CatalogFormat::Html => crunch_codegen::CatalogFormat::Html,
+    };
+    let pages = crunch_codegen::render_catalog(&events, format);
+    for (path, page) in &pages {
+        let path = output.join(path);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, page).await?;
+    }
+
+    println!(
+        "success: wrote catalog of {} events from {} services to {}",
+        events.len(),
+        members.len(),
+        output.display()
+    );
+
+    Ok(())
+}
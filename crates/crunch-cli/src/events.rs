@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use crunch_codegen::EventDescription;
+use crunch_file::Config;
+use crunch_traits::EventInfo;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor};
+
+/// An event described by the local schemas, of either [[publish]] or a [[subscription]]
+pub struct LocalEvent {
+    pub description: EventDescription,
+    pub message: MessageDescriptor,
+}
+
+impl LocalEvent {
+    pub fn event_info(&self) -> EventInfo {
+        EventInfo {
+            domain: self.description.domain.clone(),
+            entity_type: self.description.entity_type.clone(),
+            event_name: self.description.event_name.clone(),
+        }
+    }
+}
+
+/// The events of every local schema. Schemas which can't be loaded, such as subscriptions which haven't been fetched,
+/// are skipped with a warning
+pub async fn local_events(crunch_dir: &Path, config: &Config) -> anyhow::Result<Vec<LocalEvent>> {
+    let mut events = Vec::new();
+    for target in crunch_codegen::targets(config)? {
+        let schema_path = crunch_dir.join(&target.schema_path);
+        let description = match target.codegen().describe(&schema_path).await {
+            Ok(description) => description,
+            Err(e) => {
+                tracing::warn!("skipping schemas at: {}: {}", schema_path.display(), e);
+                continue;
+            }
+        };
+
+        let pool = DescriptorPool::from_file_descriptor_set(description.file_descriptor_set)?;
+        for event in description.events {
+            let message = pool
+                .get_message_by_name(&event.message)
+                .ok_or(anyhow::anyhow!(
+                    "message: {} not found in descriptors",
+                    event.message
+                ))?;
+
+            events.push(LocalEvent {
+                description: event,
+                message,
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+/// Renders the payload as json when its descriptor is known, and as hex otherwise
+pub fn render(message: Option<&MessageDescriptor>, payload: &[u8]) -> String {
+    let decoded = message.and_then(|m| DynamicMessage::decode(m.clone(), payload).ok());
+
+    match decoded.and_then(|d| serde_json::to_string_pretty(&d).ok()) {
+        Some(json) => json,
+        None => payload.iter().map(|b| format!("{:02x}", b)).collect(),
+    }
+}
+
+/// The generated code publishes events as is, though other publishers may wrap them in the crunch envelope
+pub fn unwrap_envelope(event_info: &EventInfo, payload: &[u8]) -> Vec<u8> {
+    match crunch_envelope::proto::unwrap(payload) {
+        Ok((content, metadata))
+            if metadata.domain == event_info.domain
+                && metadata.entity == event_info.entity_type =>
+        {
+            content
+        }
+        _ => payload.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_envelope() {
+        let event_info = EventInfo {
+            domain: "my-domain".into(),
+            entity_type: "my-entity".into(),
+            event_name: "MyEvent".into(),
+        };
+
+        let wrapped = crunch_envelope::proto::wrap("my-domain", "my-entity", b"content");
+        assert_eq!(b"content".to_vec(), unwrap_envelope(&event_info, &wrapped));
+
+        let other = crunch_envelope::proto::wrap("other-domain", "my-entity", b"content");
+        assert_eq!(other, unwrap_envelope(&event_info, &other));
+    }
+
+    #[test]
+    fn test_render_hex_without_descriptor() {
+        assert_eq!("0aff10", render(None, &[0x0a, 0xff, 0x10]));
+    }
+}
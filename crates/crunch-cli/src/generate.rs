@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use crunch_codegen::{StaleFile, Target};
+
+pub async fn generate(
+    crunch_dir: &Path,
+    languages: &[String],
+    target: &Target,
+) -> anyhow::Result<()> {
+    let codegen = target.codegen();
+    let rel_schema_path = crunch_dir.join(&target.schema_path);
+
+    for language in languages {
+        let rel_output_path = crunch_dir.join(target.language_output_path(language, languages)?);
+        match language.as_str() {
+            "rust" => {
+                codegen
+                    .generate_rust(&rel_schema_path, &rel_output_path)
+                    .await?
+            }
+            "go" => {
+                codegen
+                    .generate_go(&rel_schema_path, &rel_output_path)
+                    .await?
+            }
+            language => anyhow::bail!("codegen not supported for: {}", language),
+        }
+
+        println!("success: generated crunch {}", &rel_output_path.display());
+    }
+
+    Ok(())
+}
+
+/// Files of the target which are missing, differ from, or wouldn't be part of a fresh generation
+pub async fn check(
+    crunch_dir: &Path,
+    languages: &[String],
+    target: &Target,
+) -> anyhow::Result<Vec<StaleFile>> {
+    let codegen = target.codegen();
+    let rel_schema_path = crunch_dir.join(&target.schema_path);
+
+    let mut stale = Vec::new();
+    for language in languages {
+        let rel_output_path = crunch_dir.join(target.language_output_path(language, languages)?);
+        stale.extend(match language.as_str() {
+            "rust" => {
+                codegen
+                    .check_rust(&rel_schema_path, &rel_output_path)
+                    .await?
+            }
+            "go" => codegen.check_go(&rel_schema_path, &rel_output_path).await?,
+            language => anyhow::bail!("codegen not supported for: {}", language),
+        });
+    }
+
+    Ok(stale)
+}
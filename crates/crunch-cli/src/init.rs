@@ -0,0 +1,106 @@
+use std::io::IsTerminal;
+
+use inquire::validator::Validation;
+
+/// Answers the prompts of crunch init, from flags when given and otherwise by prompting for them
+pub struct Prompter {
+    interactive: bool,
+}
+
+impl Prompter {
+    /// Nothing is prompted with --yes, or when stdin isn't a terminal as in scripts and CI
+    pub fn new(yes: bool) -> Self {
+        Self {
+            interactive: !yes && std::io::stdin().is_terminal(),
+        }
+    }
+
+    /// A service or domain name, which has to be given when not interactive
+    pub fn name(&self, name: &str, value: Option<&str>, help: &str) -> anyhow::Result<String> {
+        match value {
+            Some(value) if crunch_file::is_valid_name(value) => Ok(value.to_string()),
+            Some(value) => anyhow::bail!(
+                "invalid {}: {}, it can only contain lowercase letter, numbers, - and _",
+                flag(name),
+                value
+            ),
+            None if self.interactive => Ok(inquire::Text::new(name)
+                .with_help_message(help)
+                .with_validator(validate_text)
+                .prompt()?),
+            None => Err(missing(name)),
+        }
+    }
+
+    /// A value which falls back to its default when not interactive
+    pub fn text(
+        &self,
+        name: &str,
+        value: Option<&str>,
+        help: &str,
+        default: &str,
+    ) -> anyhow::Result<String> {
+        match value {
+            Some(value) => Ok(value.to_string()),
+            None if self.interactive => Ok(inquire::Text::new(name)
+                .with_help_message(help)
+                .with_default(default)
+                .prompt()?),
+            None => Ok(default.to_string()),
+        }
+    }
+
+    /// Prompts with `defaults` selected, which are also the value when not prompting
+    pub fn multi_select(
+        &self,
+        name: &str,
+        value: Option<&[String]>,
+        options: &[&str],
+        defaults: &[&str],
+        help: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        match value {
+            Some(value) => Ok(value.to_vec()),
+            None if self.interactive => {
+                let selected: Vec<usize> = options
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| defaults.contains(o))
+                    .map(|(i, _)| i)
+                    .collect();
+
+                Ok(inquire::MultiSelect::new(name, options.to_vec())
+                    .with_default(&selected)
+                    .with_help_message(help)
+                    .prompt()?
+                    .into_iter()
+                    .map(|o| o.to_string())
+                    .collect())
+            }
+            None => Ok(defaults.iter().map(|d| d.to_string()).collect()),
+        }
+    }
+}
+
+fn flag(name: &str) -> String {
+    format!("--{}", name.replace('_', "-"))
+}
+
+fn missing(name: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "missing {}, it is required when crunch init isn't run interactively",
+        flag(name)
+    )
+}
+
+fn validate_text(
+    text: &str,
+) -> Result<Validation, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    if crunch_file::is_valid_name(text) {
+        Ok(Validation::Valid)
+    } else {
+        Ok(Validation::Invalid(
+            "a service name can only contain lowercase letter, numbers, - and _".into(),
+        ))
+    }
+}
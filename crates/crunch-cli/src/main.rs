@@ -1,12 +1,21 @@
+mod docs;
+mod events;
+mod generate;
+mod init;
 mod logging;
+mod outbox;
+mod publish;
+mod schema;
+mod tail;
+mod transport;
+mod validate;
+mod watch;
+mod workspace;
 
 use std::path::PathBuf;
 
-use anyhow::anyhow;
 use clap::{Args, Parser, Subcommand};
-use inquire::validator::Validation;
 use logging::LogArg;
-use regex::Regex;
 use tokio::io::AsyncWriteExt;
 
 #[derive(Parser, Clone)]
@@ -21,16 +30,163 @@ struct Cli {
 
 #[derive(Subcommand, Clone)]
 enum Commands {
-    Generate {},
+    Generate {
+        /// Verify the generated code is up to date instead of writing it, exits non-zero if it is stale
+        #[arg(long, conflicts_with = "watch")]
+        check: bool,
+        /// Regenerate whenever schemas or the crunch file change
+        #[arg(long)]
+        watch: bool,
+    },
     Init {
         #[command(subcommand)]
         commands: Option<InitCommands>,
+        #[arg(long)]
+        service: Option<String>,
+        #[arg(long)]
+        domain: Option<String>,
+        /// Client code to generate, comma separated
+        #[arg(long, value_delimiter = ',', value_parser = ["rust", "go"])]
+        codegen: Option<Vec<String>>,
+        /// Never prompt, fails when a required value isn't passed as a flag. Implied when stdin isn't a terminal
+        #[arg(long, short, global = true)]
+        yes: bool,
+    },
+    Schema {
+        #[command(subcommand)]
+        commands: SchemaCommands,
+    },
+    Docs {
+        #[command(subcommand)]
+        commands: DocsCommands,
+    },
+    /// Checks the crunch file for invalid names, missing schemas, duplicate entries and unknown codegen targets
+    Validate {},
+    /// Prints the events of a domain as they are published, decoded with the local schemas
+    Tail {
+        domain: String,
+        entity: Option<String>,
+        event: Option<String>,
+        #[command(flatten)]
+        transport: transport::TransportArgs,
+    },
+    /// Publishes an event of the local schemas from json, through the transport or the postgres outbox
+    Publish {
+        /// Name of the event, qualified as entity.Event or domain.entity.Event when ambiguous
+        event: String,
+        #[arg(long)]
+        json: String,
+        /// Insert the event into the postgres outbox at this url instead, for the relay of the service to publish it
+        #[arg(long)]
+        outbox: Option<String>,
+        /// Wrap the encoded message in the crunch envelope, subscribers generated by crunch read it without
+        #[arg(long)]
+        envelope: bool,
+        #[command(flatten)]
+        transport: transport::TransportArgs,
+    },
+    /// Inspects and repairs the postgres outbox of the service
+    Outbox {
+        #[command(subcommand)]
+        commands: OutboxCommands,
+        /// Url of the postgres database, defaults to DATABASE_URL
+        #[arg(long, global = true)]
+        dsn: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum OutboxCommands {
+    /// Lists the events in a state, oldest first
+    List {
+        #[arg(long, default_value = "inserted")]
+        state: outbox::StateArg,
+        #[arg(long, default_value = "100")]
+        limit: i64,
+    },
+    /// Prints an event, decoded with the local schemas
+    Show { id: String },
+    /// Marks events as inserted again, for the relay to publish them once more
+    Requeue {
+        #[arg(required_unless_present = "failed")]
+        id: Option<String>,
+        /// Requeue every failed event
+        #[arg(long, conflicts_with = "id")]
+        failed: bool,
+    },
+    /// Deletes old events, by default only those already published
+    Purge {
+        /// Delete events inserted longer ago than this, such as 30m, 12h or 7d
+        #[arg(long, value_parser = outbox::parse_duration)]
+        older_than: std::time::Duration,
+        #[arg(long, default_value = "handled")]
+        state: outbox::StateArg,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum DocsCommands {
+    /// Generates an AsyncAPI document of the events published by the service
+    Asyncapi {
+        #[arg(long, default_value = "yaml")]
+        format: docs::DocsFormat,
+        /// File to write the document to, defaults to stdout. With --workspace the directory to write them to
+        #[arg(long)]
+        output: Option<PathBuf>,
+        #[arg(long, default_value = "nats://localhost:4222")]
+        nats_url: String,
+    },
+    /// Renders a browsable catalog of the events of every crunch file in a directory
+    Catalog {
+        /// Workspace to render, either listed in its crunch-workspace.toml or found below it
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+        #[arg(long, default_value = "catalog")]
+        output: PathBuf,
+        #[arg(long, default_value = "markdown")]
+        format: docs::CatalogFormat,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum SchemaCommands {
+    /// Publishes the schemas of [[publish]] to the registry under domain/service/version
+    Publish {
+        /// Version to publish, defaults to the version of [service]
+        #[arg(long = "schema-version")]
+        version: Option<String>,
+    },
+    /// Fetches the schemas of every [[subscription]] from the registry
+    Fetch {},
+    /// Checks the [[publish]] schemas for breaking changes and the version bump they require
+    Check {
+        /// Compare against the schemas committed at this git revision, instead of the last published version
+        #[arg(long)]
+        against_ref: Option<String>,
     },
 }
 
 #[derive(Subcommand, Clone)]
 enum InitCommands {
-    Publish {},
+    Publish {
+        #[arg(long)]
+        schema_path: Option<String>,
+        #[arg(long)]
+        output_path: Option<String>,
+        /// Entity to publish for, a schema is created for it
+        #[arg(long)]
+        entity: Option<String>,
+    },
+    Subscribe {
+        #[arg(long)]
+        service: Option<String>,
+        #[arg(long)]
+        domain: Option<String>,
+        #[arg(long)]
+        version: Option<String>,
+        #[arg(long)]
+        output_path: Option<String>,
+    },
 }
 
 #[derive(Args, Clone)]
@@ -45,6 +201,27 @@ struct GlobalArgs {
         help_heading = "Global"
     )]
     crunch_file: PathBuf,
+
+    /// Run for every crunch file of the workspace at this directory, either listed in its crunch-workspace.toml or
+    /// found below it
+    #[arg(
+        long,
+        global = true,
+        help_heading = "Global",
+        num_args = 0..=1,
+        default_missing_value = "."
+    )]
+    workspace: Option<PathBuf>,
+}
+
+impl GlobalArgs {
+    /// The workspace members, or just the crunch file outside of a workspace
+    async fn members(&self) -> anyhow::Result<Vec<workspace::Member>> {
+        match &self.workspace {
+            Some(root) => workspace::members(root).await,
+            None => Ok(vec![workspace::Member::load(&self.crunch_file).await?]),
+        }
+    }
 }
 
 #[tokio::main]
@@ -53,39 +230,345 @@ async fn main() -> anyhow::Result<()> {
     cli.global_args.log.init_logging();
 
     match &cli.commands {
-        Commands::Generate {} => {
-            let config = config::get_file(&cli.global_args.crunch_file)
-                .await
-                .map_err(|e| anyhow!("failed to load config: {}", e))?
-                .get_config()
-                .map_err(|e| anyhow!("invalid config: {}", e))?;
-
-            tracing::info!("generating crunch code");
-            let codegen = crunch_codegen::Codegen::new();
-
-            if let Some(publish) = config.publish {
-                for p in &publish {
-                    let mut rel_schema_path = PathBuf::from(&p.schema_path);
-                    let mut rel_output_path = PathBuf::from(&p.output_path);
-
-                    if let Some(dir_path) = cli.global_args.crunch_file.parent() {
-                        rel_schema_path = dir_path.join(rel_schema_path);
-                        rel_output_path = dir_path.join(rel_output_path);
-                    }
+        Commands::Generate { watch: true, .. } => {
+            if cli.global_args.workspace.is_some() {
+                anyhow::bail!("--watch is not supported with --workspace, run it for each service");
+            }
+            watch::watch(&cli.global_args.crunch_file).await?;
+        }
+        Commands::Generate { check: false, .. } => {
+            for member in cli.global_args.members().await? {
+                tracing::info!(
+                    "generating crunch code for: {}",
+                    member.crunch_file.display()
+                );
 
-                    codegen
-                        .generate_rust(&rel_schema_path, &rel_output_path)
+                for target in crunch_codegen::targets(&member.config)? {
+                    generate::generate(&member.crunch_dir, &member.config.service.codegen, &target)
                         .await?;
+                }
+            }
+        }
+        Commands::Generate { check: true, .. } => {
+            let mut stale = Vec::new();
+            for member in cli.global_args.members().await? {
+                tracing::info!(
+                    "checking generated crunch code for: {}",
+                    member.crunch_file.display()
+                );
+
+                for target in crunch_codegen::targets(&member.config)? {
+                    stale.extend(
+                        generate::check(
+                            &member.crunch_dir,
+                            &member.config.service.codegen,
+                            &target,
+                        )
+                        .await?,
+                    );
+                }
+            }
+
+            for stale_file in &stale {
+                print!("{}", stale_file.diff);
+            }
+            if !stale.is_empty() {
+                anyhow::bail!(
+                    "{} generated files are out of date, run: crunch generate",
+                    stale.len()
+                );
+            }
+
+            println!("success: generated code is up to date");
+        }
+        Commands::Schema { commands } => {
+            let in_workspace = cli.global_args.workspace.is_some();
+            let mut failed = Vec::new();
+
+            for member in cli.global_args.members().await? {
+                let crunch_dir = &member.crunch_dir;
+                let config = &member.config;
+                // Services of a workspace which only subscribe have nothing to publish or check
+                let publishes = config.publish.iter().flatten().next().is_some();
+
+                let result = match commands {
+                    SchemaCommands::Publish { .. } | SchemaCommands::Check { .. }
+                        if in_workspace && !publishes =>
+                    {
+                        continue
+                    }
+                    SchemaCommands::Publish { version } => {
+                        schema::publish(crunch_dir, config, version.as_deref()).await
+                    }
+                    SchemaCommands::Fetch {} => schema::fetch(crunch_dir, config).await,
+                    SchemaCommands::Check { against_ref } => {
+                        schema::check(&member.crunch_file, config, against_ref.as_deref()).await
+                    }
+                };
+
+                // Every service of a workspace is checked, instead of stopping at the first failure
+                match result {
+                    Err(e) if in_workspace => {
+                        eprintln!("error: {}: {:#}", member.crunch_file.display(), e);
+                        failed.push(member.crunch_file);
+                    }
+                    result => result?,
+                }
+            }
+
+            if !failed.is_empty() {
+                anyhow::bail!("{} services of the workspace failed", failed.len());
+            }
+        }
+        Commands::Docs { commands } => match commands {
+            DocsCommands::Asyncapi {
+                format,
+                output,
+                nats_url,
+            } => {
+                let in_workspace = cli.global_args.workspace.is_some();
+                if in_workspace && output.is_none() {
+                    anyhow::bail!("--output is required with --workspace, it is the directory to write a document per service to");
+                }
+
+                for member in cli.global_args.members().await? {
+                    if in_workspace && member.config.publish.is_none() {
+                        continue;
+                    }
+
+                    let document =
+                        docs::asyncapi(&member.crunch_dir, &member.config, nats_url, format)
+                            .await?;
+                    let output = match output {
+                        Some(output) if in_workspace => Some(output.join(format!(
+                            "{}.{}",
+                            member.config.service.service,
+                            format.extension()
+                        ))),
+                        output => output.clone(),
+                    };
+                    docs::write_output(output.as_deref(), &document).await?;
+                }
+            }
+            DocsCommands::Catalog {
+                dir,
+                output,
+                format,
+            } => {
+                let root = cli.global_args.workspace.as_ref().unwrap_or(dir);
+                let members = workspace::members(root).await?;
+                docs::catalog(&members, output, format).await?
+            }
+        },
+        Commands::Validate {} => {
+            let crunch_files = match &cli.global_args.workspace {
+                Some(root) => workspace::crunch_files(root).await?,
+                None => vec![cli.global_args.crunch_file.clone()],
+            };
 
-                    println!("success: generated crunch {}", &rel_output_path.display());
+            let mut problems = 0;
+            for crunch_file in &crunch_files {
+                problems += validate::validate(crunch_file).await?;
+            }
+            if problems > 0 {
+                anyhow::bail!("found {} problems", problems);
+            }
+
+            println!("success: {} crunch files are valid", crunch_files.len());
+        }
+        Commands::Tail {
+            domain,
+            entity,
+            event,
+            transport,
+        } => {
+            let config = config::get_config(&cli.global_args.crunch_file).await?;
+            let local_events =
+                events::local_events(&config::crunch_dir(&cli.global_args.crunch_file), &config)
+                    .await?;
+
+            tail::tail(
+                transport.connect().await?,
+                local_events,
+                &tail::Filter {
+                    domain,
+                    entity: entity.as_deref(),
+                    event: event.as_deref(),
+                },
+            )
+            .await?;
+        }
+        Commands::Publish {
+            event,
+            json,
+            outbox,
+            envelope,
+            transport,
+        } => {
+            let config = config::get_config(&cli.global_args.crunch_file).await?;
+            let local_events =
+                events::local_events(&config::crunch_dir(&cli.global_args.crunch_file), &config)
+                    .await?;
+
+            let event = publish::find_event(&local_events, event)?;
+            let content = publish::encode(event, json, *envelope)?;
+            let destination = match outbox {
+                Some(dsn) => publish::Destination::Outbox(
+                    crunch_postgres::PostgresPersistence::new(dsn).await?,
+                ),
+                None => publish::Destination::Transport(transport.connect().await?),
+            };
+
+            publish::publish(event, content, &destination).await?;
+        }
+        Commands::Outbox { commands, dsn } => {
+            let persistence = outbox::connect(dsn.as_deref()).await?;
+
+            match commands {
+                OutboxCommands::List { state, limit } => {
+                    outbox::list(&persistence, *state, *limit).await?
+                }
+                OutboxCommands::Show { id } => {
+                    // The content is shown as hex without a crunch file describing it
+                    let local_events = match config::get_config(&cli.global_args.crunch_file).await
+                    {
+                        Ok(config) => {
+                            events::local_events(
+                                &config::crunch_dir(&cli.global_args.crunch_file),
+                                &config,
+                            )
+                            .await?
+                        }
+                        Err(_) => Vec::new(),
+                    };
+
+                    outbox::show(&persistence, id, &local_events).await?
+                }
+                OutboxCommands::Requeue { id: Some(id), .. } => {
+                    if !persistence.requeue(id.parse()?).await? {
+                        anyhow::bail!("no event: {} in the outbox", id);
+                    }
+                    println!("success: requeued {}", id);
+                }
+                OutboxCommands::Requeue { id: None, .. } => {
+                    // The relay keeps retrying events itself, only events marked failed outside of crunch are found
+                    let requeued = persistence.requeue_failed().await?;
+                    println!("success: requeued {} failed events", requeued);
+                    if requeued == 0 {
+                        println!(
+                            "note: crunch doesn't mark events as failed itself, only events marked failed by other tools are requeued"
+                        );
+                    }
+                }
+                OutboxCommands::Purge { older_than, state } => {
+                    let purged = persistence.purge((*state).into(), *older_than).await?;
+                    println!("success: purged {} events", purged);
                 }
             }
         }
         Commands::Init {
             commands: Some(commands),
+            yes,
+            ..
         } => match commands {
-            InitCommands::Publish {} => {
-                match config::get_file(&cli.global_args.crunch_file).await {
+            InitCommands::Publish {
+                schema_path,
+                output_path,
+                entity,
+            } => match config::get_file(&cli.global_args.crunch_file).await {
+                Err(_) => {
+                    anyhow::bail!(
+                        "config file not found: {}",
+                        &cli.global_args
+                            .crunch_file
+                            .canonicalize()
+                            .unwrap_or(cli.global_args.crunch_file)
+                            .display()
+                    )
+                }
+                Ok(mut config) => {
+                    let prompter = init::Prompter::new(*yes);
+                    let schema_path = prompter.text(
+                        "schema_path",
+                        schema_path.as_deref(),
+                        "please select where you want your schema files to be placed",
+                        "schemas/crunch",
+                    )?;
+                    let output_path = prompter.text(
+                        "output_path",
+                        output_path.as_deref(),
+                        "please select where you want your generated files to be placed",
+                        "src/gencrunch",
+                    )?;
+                    let entity = prompter.name(
+                        "entity",
+                        entity.as_deref(),
+                        "please set which entity you want to publish for",
+                    )?;
+
+                    let config = config.add_publish(&schema_path, &output_path, &[&entity]);
+                    config.write_file(&cli.global_args.crunch_file).await?;
+
+                    let schema_output_path = cli
+                        .global_args
+                        .crunch_file
+                        .parent()
+                        .unwrap_or(&PathBuf::from(""))
+                        .join(&schema_path)
+                        .join(format!("{}.proto", entity));
+                    if let Some(dir) = schema_output_path.parent() {
+                        if !dir.exists() {
+                            tokio::fs::create_dir_all(dir).await?;
+                        }
+                    }
+                    let config = config.get_config()?;
+                    let mut schema_file = tokio::fs::File::create(schema_output_path).await?;
+                    schema_file
+                        .write_all(
+                            format!(
+                                r#"syntax = "proto3";
+
+import "crunch/options.proto";
+
+package {}.{};
+
+message MyEvent {{
+    option (crunch.event) = true;
+
+    string my_field = 1;
+}}
+"#,
+                                config.service.domain.replace('-', "_"),
+                                entity.replace('-', "_")
+                            )
+                            .as_bytes(),
+                        )
+                        .await?;
+
+                    let output_path = if let Some(dir) = &cli.global_args.crunch_file.parent() {
+                        if dir.display().to_string() == "" {
+                            schema_path.to_string()
+                        } else {
+                            format!(
+                                "{}/{}",
+                                dir.display().to_string().trim_end_matches('/'),
+                                schema_path.trim_start_matches('/')
+                            )
+                        }
+                    } else {
+                        schema_path.to_string()
+                    };
+
+                    println!("Success: added publish, check schema at: {output_path}");
+                }
+            },
+            InitCommands::Subscribe {
+                service,
+                domain,
+                version,
+                output_path,
+            } => {
+                let mut config = match config::get_file(&cli.global_args.crunch_file).await {
                     Err(_) => {
                         anyhow::bail!(
                             "config file not found: {}",
@@ -96,82 +579,51 @@ async fn main() -> anyhow::Result<()> {
                                 .display()
                         )
                     }
-                    Ok(mut config) => {
-                        let schema_path = inquire::Text::new("schema_path")
-                            .with_help_message(
-                                "please select where you want your schema files to be placed",
-                            )
-                            .with_default("schemas/crunch")
-                            .prompt()?;
-                        let output_path = inquire::Text::new("output_path")
-                            .with_help_message(
-                                "please select where you want your generated files to be placed",
-                            )
-                            .with_default("src/gencrunch")
-                            .prompt()?;
-                        let entity = inquire::Text::new("entity")
-                            .with_help_message("please set which entity you want to publish for")
-                            .with_placeholder("some-entity")
-                            .prompt_skippable()?;
-
-                        if let Some(entity) = entity {
-                            let config = config.add_publish(&schema_path, &output_path, &[&entity]);
-                            config.write_file(&cli.global_args.crunch_file).await?;
-
-                            let schema_output_path = cli
-                                .global_args
-                                .crunch_file
-                                .parent()
-                                .unwrap_or(&PathBuf::from(""))
-                                .join(&schema_path)
-                                .join(format!("{}.proto", entity));
-                            if let Some(dir) = schema_output_path.parent() {
-                                if !dir.exists() {
-                                    tokio::fs::create_dir_all(dir).await?;
-                                }
-                            }
-                            let config = config.get_config()?;
-                            let mut schema_file =
-                                tokio::fs::File::create(schema_output_path).await?;
-                            schema_file
-                                .write_all(
-                                    format!(
-                                        r#"syntax = "proto3";
+                    Ok(config) => config,
+                };
 
-package {}.{};
+                let prompter = init::Prompter::new(*yes);
+                let service = prompter.name(
+                    "service",
+                    service.as_deref(),
+                    "please insert the service you want to subscribe to",
+                )?;
+                let domain = prompter.name(
+                    "domain",
+                    domain.as_deref(),
+                    "please insert the domain of the service",
+                )?;
+                let version = prompter.text(
+                    "version",
+                    version.as_deref(),
+                    "please select which version of the schemas to use",
+                    "1.0.0",
+                )?;
+                let output_path = prompter.text(
+                    "output_path",
+                    output_path.as_deref(),
+                    "please select where you want your generated files to be placed",
+                    &format!("src/gencrunch_{}", service.replace('-', "_")),
+                )?;
 
-message MyEvent {{
-    string my_field = 1;
-}}
-"#,
-                                        config.service.domain.replace('-', "_"),
-                                        entity.replace('-', "_")
-                                    )
-                                    .as_bytes(),
-                                )
-                                .await?;
-                        }
+                let config = config.add_subscription(&service, &domain, &version, &output_path);
+                config.write_file(&cli.global_args.crunch_file).await?;
 
-                        let output_path = if let Some(dir) = &cli.global_args.crunch_file.parent() {
-                            if dir.display().to_string() == "" {
-                                schema_path.to_string()
-                            } else {
-                                format!(
-                                    "{}/{}",
-                                    dir.display().to_string().trim_end_matches('/'),
-                                    schema_path.trim_start_matches('/')
-                                )
-                            }
-                        } else {
-                            schema_path.to_string()
-                        };
-
-                        println!("Success: added publish, check schema at: {output_path}");
-                    }
+                let config = config.get_config()?;
+                if config.registry.is_none() {
+                    println!("note: no [registry] is configured, set schema-path on the subscription to point at the schemas");
                 }
+
+                println!("Success: added subscription to {domain}/{service}@{version}, run crunch generate to generate its events");
             }
         },
-        Commands::Init { commands: None } => {
+        Commands::Init {
+            commands: None,
+            service,
+            domain,
+            codegen,
+            yes,
+        } => {
             if (config::get_file(&cli.global_args.crunch_file).await).is_ok() {
                 anyhow::bail!("config file already exists")
             }
@@ -183,38 +635,28 @@ message MyEvent {{
             if file_name != ".crunch.toml" {
                 anyhow::bail!("--crunch-file always has to end with file: .crunch.toml");
             }
+
+            let prompter = init::Prompter::new(*yes);
+            let service = prompter.name(
+                "service",
+                service.as_deref(),
+                "please insert your service name",
+            )?;
+            let domain = prompter.name("domain", domain.as_deref(), "please insert your domain")?;
+            let codegen = prompter.multi_select(
+                "codegen",
+                codegen.as_deref(),
+                &["rust", "go"],
+                &["rust"],
+                "which types of client code should be generated for you?",
+            )?;
+
             if let Some(dir) = path.parent() {
                 if !dir.exists() {
                     tokio::fs::create_dir_all(dir).await?;
                 }
             }
 
-            fn validate_text(
-                text: &str,
-            ) -> Result<Validation, Box<dyn std::error::Error + Send + Sync + 'static>>
-            {
-                let regex = Regex::new("^[a-z0-9-_]+$").expect("is required to be valid regex");
-                if regex.is_match(text) {
-                    Ok(Validation::Valid)
-                } else {
-                    Ok(Validation::Invalid(
-                        "a service name can only contain lowercase letter, numbers, - and _".into(),
-                    ))
-                }
-            }
-
-            let service = inquire::Text::new("service")
-                .with_help_message("please insert your service name")
-                .with_validator(validate_text)
-                .prompt()?;
-            let domain = inquire::Text::new("domain")
-                .with_help_message("please insert your domain")
-                .with_validator(validate_text)
-                .prompt()?;
-            let codegen = inquire::MultiSelect::new("codegen", vec!["rust", "go"])
-                .with_help_message("which types of client code should be generated for you?")
-                .prompt()?;
-
             let mut crunch_file = tokio::fs::File::create(path).await?;
             crunch_file
                 .write_all(
@@ -242,9 +684,26 @@ codegen = [{}]
 }
 
 mod config {
-    pub async fn get_file(path: &std::path::Path) -> anyhow::Result<crunch_file::File> {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::anyhow;
+
+    pub async fn get_file(path: &Path) -> anyhow::Result<crunch_file::File> {
         let file = crunch_file::File::parse_file(path).await?;
 
         Ok(file)
     }
+
+    pub async fn get_config(path: &Path) -> anyhow::Result<crunch_file::Config> {
+        get_file(path)
+            .await
+            .map_err(|e| anyhow!("failed to load config: {}", e))?
+            .get_config()
+            .map_err(|e| anyhow!("invalid config: {}, run: crunch validate for details", e))
+    }
+
+    /// Paths in the crunch file are relative to the directory it is placed in
+    pub fn crunch_dir(path: &Path) -> PathBuf {
+        path.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+    }
 }
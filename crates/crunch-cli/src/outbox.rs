@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use crunch_postgres::{OutboxEntry, OutboxState, PostgresPersistence};
+
+use crate::events::{self, LocalEvent};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StateArg {
+    Inserted,
+    Handled,
+    Failed,
+}
+
+impl From<StateArg> for OutboxState {
+    fn from(value: StateArg) -> Self {
+        match value {
+            StateArg::Inserted => OutboxState::Inserted,
+            StateArg::Handled => OutboxState::Handled,
+            StateArg::Failed => OutboxState::Failed,
+        }
+    }
+}
+
+/// Connects to the outbox at `dsn`, or the DATABASE_URL as used by the service
+pub async fn connect(dsn: Option<&str>) -> anyhow::Result<PostgresPersistence> {
+    match dsn {
+        Some(dsn) => PostgresPersistence::new(dsn).await,
+        None => PostgresPersistence::new_from_env()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}, or pass --dsn", e)),
+    }
+}
+
+pub async fn list(
+    persistence: &PostgresPersistence,
+    state: StateArg,
+    limit: i64,
+) -> anyhow::Result<()> {
+    for entry in persistence.list(state.into(), limit).await? {
+        println!(
+            "{}  {}  {}  {}.{}.{}",
+            entry.id,
+            entry.inserted_time.to_rfc3339(),
+            entry.state,
+            entry.event_info.domain,
+            entry.event_info.entity_type,
+            entry.event_info.event_name
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the event, with its content decoded by the local schemas if they describe it
+pub async fn show(
+    persistence: &PostgresPersistence,
+    id: &str,
+    local_events: &[LocalEvent],
+) -> anyhow::Result<()> {
+    let entry = persistence
+        .show(id.parse()?)
+        .await?
+        .ok_or(anyhow::anyhow!("no event: {} in the outbox", id))?;
+
+    println!("{}", render(&entry, local_events));
+
+    Ok(())
+}
+
+fn render(entry: &OutboxEntry, local_events: &[LocalEvent]) -> String {
+    let message = local_events
+        .iter()
+        .find(|e| {
+            e.description.domain == entry.event_info.domain
+                && e.description.entity_type == entry.event_info.entity_type
+                && e.description.event_name == entry.event_info.event_name
+        })
+        .map(|e| &e.message);
+    let content = events::unwrap_envelope(&entry.event_info, &entry.content);
+
+    format!(
+        "id: {}\ninserted: {}\nstate: {}\nevent: {}.{}.{}\n{}",
+        entry.id,
+        entry.inserted_time.to_rfc3339(),
+        entry.state,
+        entry.event_info.domain,
+        entry.event_info.entity_type,
+        entry.event_info.event_name,
+        events::render(message, &content)
+    )
+}
+
+/// Parses durations such as `30m`, `12h` or `7d`
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or("missing unit, use s, m, h or d")?;
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration: {}", value))?;
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        unit => return Err(format!("unknown unit: {}, use s, m, h or d", unit)),
+    };
+
+    Ok(Duration::from_secs(amount * seconds))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Ok(Duration::from_secs(30)), parse_duration("30s"));
+        assert_eq!(Ok(Duration::from_secs(12 * 60 * 60)), parse_duration("12h"));
+        assert_eq!(
+            Ok(Duration::from_secs(7 * 24 * 60 * 60)),
+            parse_duration("7d")
+        );
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("7w").is_err());
+    }
+}
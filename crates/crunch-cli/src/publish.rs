@@ -0,0 +1,206 @@
+use crunch_traits::{DynTransport, Persistence};
+use prost::Message;
+use prost_reflect::DynamicMessage;
+
+use crate::events::LocalEvent;
+
+/// Where a published event is sent
+pub enum Destination {
+    Transport(DynTransport),
+    /// Inserted into the outbox, from which the relay of the service publishes it
+    Outbox(crunch_postgres::PostgresPersistence),
+}
+
+/// The local event named by `name`, either by its event name, or qualified as `entity.Event` or `domain.entity.Event`
+pub fn find_event<'a>(
+    local_events: &'a [LocalEvent],
+    name: &str,
+) -> anyhow::Result<&'a LocalEvent> {
+    let matches = local_events
+        .iter()
+        .filter(|e| {
+            let d = &e.description;
+            name == d.event_name
+                || name == format!("{}.{}", d.entity_type, d.event_name)
+                || name == format!("{}.{}.{}", d.domain, d.entity_type, d.event_name)
+        })
+        .collect::<Vec<_>>();
+
+    match matches.as_slice() {
+        [event] => Ok(event),
+        [] => anyhow::bail!("no event: {} found in the local schemas", name),
+        events => anyhow::bail!(
+            "event: {} is ambiguous, qualify it as one of: {}",
+            name,
+            events
+                .iter()
+                .map(|e| format!(
+                    "{}.{}.{}",
+                    e.description.domain, e.description.entity_type, e.description.event_name
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Encodes the json as the protobuf message of the event, as the generated code publishes it. With `envelope` it is
+/// wrapped in the crunch envelope instead
+pub fn encode(event: &LocalEvent, json: &str, envelope: bool) -> anyhow::Result<Vec<u8>> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let message = DynamicMessage::deserialize(event.message.clone(), &mut deserializer)
+        .map_err(|e| anyhow::anyhow!("invalid json for: {}: {}", event.description.message, e))?;
+    deserializer.end()?;
+
+    let content = message.encode_to_vec();
+    if !envelope {
+        return Ok(content);
+    }
+
+    Ok(crunch_envelope::proto::wrap(
+        &event.description.domain,
+        &event.description.entity_type,
+        &content,
+    ))
+}
+
+pub async fn publish(
+    event: &LocalEvent,
+    content: Vec<u8>,
+    destination: &Destination,
+) -> anyhow::Result<()> {
+    let event_info = event.event_info();
+    match destination {
+        Destination::Transport(transport) => {
+            transport.publish(&event_info, content).await?;
+            println!("success: published {}", event.description.subject());
+        }
+        Destination::Outbox(persistence) => {
+            persistence.insert(&event_info, content).await?;
+            println!(
+                "success: inserted {} into the outbox",
+                event.description.subject()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crunch_codegen::EventDescription;
+    use prost_reflect::DescriptorPool;
+    use prost_types::{
+        field_descriptor_proto::Type, DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+    };
+
+    use super::*;
+
+    // Mirrors the message and `Deserializer` crunch generate emits for `MyEvent`
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct MyEvent {
+        #[prost(string, tag = "1")]
+        my_field: String,
+    }
+
+    impl crunch_traits::Deserializer for MyEvent {
+        fn deserialize(raw: Vec<u8>) -> Result<Self, crunch_traits::errors::DeserializeError>
+        where
+            Self: Sized,
+        {
+            let output = Self::decode(raw.as_slice())
+                .map_err(crunch_traits::errors::DeserializeError::ProtoErr)?;
+            Ok(output)
+        }
+    }
+
+    fn local_event(domain: &str, entity: &str) -> LocalEvent {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(FileDescriptorProto {
+            name: Some(format!("{entity}.proto")),
+            package: Some(format!("{domain}.{entity}")),
+            message_type: vec![DescriptorProto {
+                name: Some("MyEvent".into()),
+                field: vec![FieldDescriptorProto {
+                    name: Some("my_field".into()),
+                    number: Some(1),
+                    r#type: Some(Type::String as i32),
+                    json_name: Some("myField".into()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            syntax: Some("proto3".into()),
+            ..Default::default()
+        })
+        .expect("descriptor to be valid");
+
+        let message = format!("{domain}.{entity}.MyEvent");
+        LocalEvent {
+            message: pool
+                .get_message_by_name(&message)
+                .expect("message to exist"),
+            description: EventDescription {
+                domain: domain.into(),
+                entity_type: entity.into(),
+                event_name: "MyEvent".into(),
+                message,
+                file: format!("{entity}.proto"),
+            },
+        }
+    }
+
+    #[test]
+    fn test_find_event() -> anyhow::Result<()> {
+        let events = vec![
+            local_event("my_domain", "my_entity"),
+            local_event("my_domain", "other_entity"),
+        ];
+
+        assert!(find_event(&events, "MyEvent").is_err());
+        assert!(find_event(&events, "UnknownEvent").is_err());
+        assert_eq!(
+            "other_entity",
+            find_event(&events, "other_entity.MyEvent")?
+                .description
+                .entity_type
+        );
+        assert_eq!(
+            "my_entity",
+            find_event(&events, "my_domain.my_entity.MyEvent")?
+                .description
+                .entity_type
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode() -> anyhow::Result<()> {
+        let event = local_event("my_domain", "my_entity");
+
+        let raw = encode(&event, r#"{"myField": "value"}"#, false)?;
+        assert_eq!(b"\x0a\x05value".to_vec(), raw);
+
+        let wrapped = encode(&event, r#"{"myField": "value"}"#, true)?;
+        let (content, metadata) = crunch_envelope::proto::unwrap(&wrapped)?;
+        assert_eq!(raw, content);
+        assert_eq!("my_domain", metadata.domain);
+
+        assert!(encode(&event, r#"{"unknown": 1}"#, false).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_is_read_by_subscribers() -> anyhow::Result<()> {
+        let event = local_event("my_domain", "my_entity");
+
+        let content = encode(&event, r#"{"myField": "value"}"#, false)?;
+        let decoded = <MyEvent as crunch_traits::Deserializer>::deserialize(content)?;
+        assert_eq!("value", decoded.my_field);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+use std::path::{Component, Path};
+
+use crunch_codegen::ChangeKind;
+use crunch_file::{Bump, Config};
+use crunch_registry::SchemaRef;
+use prost_types::FileDescriptorSet;
+
+use crate::config::crunch_dir;
+
+fn registry(
+    crunch_dir: &Path,
+    config: &Config,
+) -> anyhow::Result<crunch_registry::DynSchemaRegistry> {
+    match &config.registry {
+        Some(registry) => crunch_registry::from_config(registry, crunch_dir),
+        None => anyhow::bail!("no [registry] configured in crunch file"),
+    }
+}
+
+pub async fn publish(
+    crunch_dir: &Path,
+    config: &Config,
+    version: Option<&str>,
+) -> anyhow::Result<()> {
+    let version = match version.or(config.service.version.as_deref()) {
+        Some(version) => version,
+        None => anyhow::bail!(
+            "no version to publish, set version in [service] or pass --schema-version"
+        ),
+    };
+
+    let schema_paths = config
+        .publish
+        .iter()
+        .flatten()
+        .map(|p| crunch_dir.join(&p.schema_path))
+        .collect::<Vec<_>>();
+    if schema_paths.is_empty() {
+        anyhow::bail!("no [[publish]] configured in crunch file");
+    }
+
+    let schema = SchemaRef::new(&config.service.domain, &config.service.service, version);
+    registry(crunch_dir, config)?
+        .publish(&schema, &schema_paths)
+        .await?;
+
+    println!("success: published schema {}", schema);
+
+    Ok(())
+}
+
+pub async fn fetch(crunch_dir: &Path, config: &Config) -> anyhow::Result<()> {
+    let registry_config = config.registry.as_ref();
+    let mut subscriptions = Vec::new();
+    for subscription in config.subscription.iter().flatten() {
+        if subscription.schema_path.is_some() {
+            tracing::debug!(
+                "skipping subscription: {}/{}, it sets schema-path",
+                subscription.domain,
+                subscription.service
+            );
+            continue;
+        }
+        subscriptions.push(subscription);
+    }
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    let registry = registry(crunch_dir, config)?;
+    for subscription in subscriptions {
+        let schema = SchemaRef::new(
+            &subscription.domain,
+            &subscription.service,
+            &subscription.version,
+        );
+        let destination = crunch_dir.join(subscription.resolve_schema_path(registry_config)?);
+        registry.fetch(&schema, &destination).await?;
+
+        println!(
+            "success: fetched schema {} into {}",
+            schema,
+            destination.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Schemas to compare the current ones against
+struct Baseline {
+    version: Option<semver::Version>,
+    file_descriptor_set: FileDescriptorSet,
+}
+
+pub async fn check(
+    crunch_file: &Path,
+    config: &Config,
+    against_ref: Option<&str>,
+) -> anyhow::Result<()> {
+    let crunch_dir = crunch_dir(crunch_file);
+    let version = config
+        .service
+        .version
+        .as_deref()
+        .map(semver::Version::parse)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("invalid version in [service]: {}", e))?;
+
+    let mut current = FileDescriptorSet::default();
+    for publish in config.publish.iter().flatten() {
+        current.file.extend(
+            crunch_codegen::Codegen::new()
+                .descriptor_set(&crunch_dir.join(&publish.schema_path))
+                .await?
+                .file,
+        );
+    }
+
+    let baseline = match against_ref {
+        Some(git_ref) => baseline_from_git(crunch_file, config, git_ref).await?,
+        None => match baseline_from_registry(&crunch_dir, config, version.as_ref()).await? {
+            Some(baseline) => baseline,
+            None => {
+                println!("success: no published version to check against");
+                return Ok(());
+            }
+        },
+    };
+
+    let changes = crunch_codegen::compare(&baseline.file_descriptor_set, &current);
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    let rules = config.compatibility.clone().unwrap_or_default();
+    let required = changes
+        .iter()
+        .map(|c| match c.kind {
+            ChangeKind::Breaking => rules.breaking,
+            ChangeKind::Addition => rules.additions,
+            ChangeKind::Compatible => rules.compatible,
+        })
+        .max()
+        .unwrap_or(Bump::None);
+
+    let bumped = match (&baseline.version, &version) {
+        (Some(previous), Some(current)) => bump(previous, current)?,
+        _ => Bump::None,
+    };
+    let version_display = |v: &Option<semver::Version>| {
+        v.as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "unknown".into())
+    };
+
+    if bumped < required {
+        anyhow::bail!(
+            "schema changes require a {} version bump, version went from {} to {}",
+            required,
+            version_display(&baseline.version),
+            version_display(&version),
+        );
+    }
+
+    println!(
+        "success: {} schema changes, version bump {} to {} is sufficient",
+        changes.len(),
+        version_display(&baseline.version),
+        version_display(&version),
+    );
+
+    Ok(())
+}
+
+fn bump(previous: &semver::Version, current: &semver::Version) -> anyhow::Result<Bump> {
+    if current < previous {
+        anyhow::bail!(
+            "version: {} is lower than the previous version: {}",
+            current,
+            previous
+        );
+    }
+
+    Ok(if current.major != previous.major {
+        Bump::Major
+    } else if current.minor != previous.minor {
+        Bump::Minor
+    } else if current.patch != previous.patch {
+        Bump::Patch
+    } else {
+        Bump::None
+    })
+}
+
+/// The latest published version, not newer than the current version
+async fn baseline_from_registry(
+    crunch_dir: &Path,
+    config: &Config,
+    version: Option<&semver::Version>,
+) -> anyhow::Result<Option<Baseline>> {
+    let registry = registry(crunch_dir, config)?;
+
+    let previous = registry
+        .versions(&config.service.domain, &config.service.service)
+        .await?
+        .iter()
+        .filter_map(|v| semver::Version::parse(v).ok())
+        .filter(|v| version.map(|version| v <= version).unwrap_or(true))
+        .max();
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+
+    let dir = tempfile::tempdir()?;
+    let schema = SchemaRef::new(
+        &config.service.domain,
+        &config.service.service,
+        previous.to_string(),
+    );
+    registry.fetch(&schema, dir.path()).await?;
+
+    tracing::debug!("checking against published schema: {}", schema);
+    Ok(Some(Baseline {
+        version: Some(previous),
+        file_descriptor_set: crunch_codegen::Codegen::new()
+            .descriptor_set(dir.path())
+            .await?,
+    }))
+}
+
+/// The schemas and version as committed at `git_ref`
+async fn baseline_from_git(
+    crunch_file: &Path,
+    config: &Config,
+    git_ref: &str,
+) -> anyhow::Result<Baseline> {
+    let crunch_dir = crunch_dir(crunch_file);
+    let crunch_file_name = crunch_file
+        .file_name()
+        .ok_or(anyhow::anyhow!("not a valid file name"))?
+        .to_string_lossy();
+
+    let version = match git_show(&crunch_dir, git_ref, &crunch_file_name).await {
+        Ok(content) => crunch_file::File::parse(&String::from_utf8(content)?)
+            .await?
+            .get_config()?
+            .service
+            .version
+            .map(|v| semver::Version::parse(&v))
+            .transpose()?,
+        Err(e) => {
+            tracing::debug!("no crunch file at: {}, {}", git_ref, e);
+            None
+        }
+    };
+
+    let mut file_descriptor_set = FileDescriptorSet::default();
+    for publish in config.publish.iter().flatten() {
+        let dir = tempfile::tempdir()?;
+        let schema_path = git_path(&publish.schema_path);
+        let files = git(
+            &crunch_dir,
+            &[
+                "ls-tree",
+                "-r",
+                "--name-only",
+                git_ref,
+                "--",
+                if schema_path.is_empty() {
+                    "."
+                } else {
+                    &schema_path
+                },
+            ],
+        )
+        .await?;
+
+        let mut found = false;
+        for file in String::from_utf8(files)?
+            .lines()
+            .filter(|f| f.ends_with(".proto"))
+        {
+            let content = git_show(&crunch_dir, git_ref, file).await?;
+            let destination = dir.path().join(Path::new(file).strip_prefix(&schema_path)?);
+            if let Some(parent) = destination.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(destination, content).await?;
+            found = true;
+        }
+
+        // Schemas which didn't exist yet are all additions
+        if found {
+            file_descriptor_set.file.extend(
+                crunch_codegen::Codegen::new()
+                    .descriptor_set(dir.path())
+                    .await?
+                    .file,
+            );
+        }
+    }
+
+    Ok(Baseline {
+        version,
+        file_descriptor_set,
+    })
+}
+
+/// `path` as git lists it, i.e. `schemas` for `./schemas/`
+fn git_path(path: &str) -> String {
+    Path::new(path)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn git_show(dir: &Path, git_ref: &str, path: &str) -> anyhow::Result<Vec<u8>> {
+    git(dir, &["show", &format!("{}:./{}", git_ref, path)]).await
+}
+
+async fn git(dir: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_git_path() {
+        assert_eq!("schemas", git_path("schemas"));
+        assert_eq!("schemas", git_path("./schemas"));
+        assert_eq!("schemas", git_path("schemas/"));
+        assert_eq!("schemas/events", git_path("./schemas//events/"));
+        assert_eq!("", git_path("./"));
+    }
+}
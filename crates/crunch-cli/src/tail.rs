@@ -0,0 +1,90 @@
+use crunch_traits::{DynTransport, EventInfo};
+use futures::StreamExt;
+use prost_reflect::MessageDescriptor;
+
+use crate::events::{self, LocalEvent};
+
+/// The events to tail, those of the local schemas matching the filter
+pub struct Filter<'a> {
+    pub domain: &'a str,
+    pub entity: Option<&'a str>,
+    pub event: Option<&'a str>,
+}
+
+impl Filter<'_> {
+    fn matches(&self, event: &LocalEvent) -> bool {
+        event.description.domain == self.domain
+            && self
+                .entity
+                .is_none_or(|e| e == event.description.entity_type)
+            && self.event.is_none_or(|e| e == event.description.event_name)
+    }
+}
+
+/// Subscribes to every event matching the filter, and prints them as they arrive until interrupted.
+///
+/// An event which isn't in the local schemas can still be tailed by naming it fully, it is then printed as hex
+pub async fn tail(
+    transport: DynTransport,
+    local_events: Vec<LocalEvent>,
+    filter: &Filter<'_>,
+) -> anyhow::Result<()> {
+    let mut subscriptions: Vec<(EventInfo, Option<MessageDescriptor>)> = local_events
+        .iter()
+        .filter(|e| filter.matches(e))
+        .map(|e| (e.event_info(), Some(e.message.clone())))
+        .collect();
+
+    if subscriptions.is_empty() {
+        match (filter.entity, filter.event) {
+            (Some(entity), Some(event)) => {
+                tracing::warn!("no local schema describes the event, printing it as hex");
+                subscriptions.push((
+                    EventInfo {
+                        domain: filter.domain.to_string(),
+                        entity_type: entity.to_string(),
+                        event_name: event.to_string(),
+                    },
+                    None,
+                ));
+            }
+            _ => anyhow::bail!(
+                "no events of the local schemas match: {}, name both the entity and event to tail it anyway",
+                [Some(filter.domain), filter.entity, filter.event]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(".")
+            ),
+        }
+    }
+
+    let mut streams = Vec::new();
+    for (event_info, message) in subscriptions {
+        let stream = transport
+            .subscriber(&event_info)
+            .await?
+            .ok_or(anyhow::anyhow!("failed to subscribe to: {}", event_info))?;
+
+        eprintln!(
+            "tailing: {}.{}.{}",
+            event_info.domain, event_info.entity_type, event_info.event_name
+        );
+        streams.push(stream.map(move |payload| (event_info.clone(), message.clone(), payload)));
+    }
+
+    let mut events = futures::stream::select_all(streams);
+    while let Some((event_info, message, payload)) = events.next().await {
+        let content = events::unwrap_envelope(&event_info, &payload);
+
+        println!(
+            "{}.{}.{}\n{}",
+            event_info.domain,
+            event_info.entity_type,
+            event_info.event_name,
+            events::render(message.as_ref(), &content)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use clap::{Args, ValueEnum};
+use crunch_nats::{NatsConnectCredentials, NatsConnectOptions, NatsTransport};
+use crunch_nodata::NoDataTransport;
+use crunch_traits::DynTransport;
+
+#[derive(Clone, ValueEnum)]
+pub enum TransportKind {
+    Nats,
+    Nodata,
+}
+
+#[derive(Args, Clone)]
+pub struct TransportArgs {
+    #[arg(long, default_value = "nats")]
+    transport: TransportKind,
+    /// Address of the transport, defaults to a local nats server
+    #[arg(long)]
+    url: Option<String>,
+    #[arg(long, requires = "nats_pass")]
+    nats_user: Option<String>,
+    #[arg(long, requires = "nats_user")]
+    nats_pass: Option<String>,
+}
+
+impl TransportArgs {
+    pub async fn connect(&self) -> anyhow::Result<DynTransport> {
+        let transport: DynTransport = match self.transport {
+            TransportKind::Nats => {
+                let credentials = match (&self.nats_user, &self.nats_pass) {
+                    (Some(user), Some(pass)) => NatsConnectCredentials::UserPass { user, pass },
+                    _ => NatsConnectCredentials::None,
+                };
+
+                Arc::new(
+                    NatsTransport::new(NatsConnectOptions {
+                        host: self.url.as_deref().unwrap_or("localhost:4222"),
+                        credentials,
+                    })
+                    .await?,
+                )
+            }
+            TransportKind::Nodata => match &self.url {
+                Some(url) => Arc::new(NoDataTransport::new(url)),
+                None => anyhow::bail!("--url is required with --transport nodata"),
+            },
+        };
+
+        Ok(transport)
+    }
+}
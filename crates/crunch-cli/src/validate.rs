@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use crunch_file::Diagnostic;
+use miette::{LabeledSpan, MietteDiagnostic, NamedSource, Report, Severity};
+
+use crate::config;
+
+/// Prints the problems of the crunch file, and returns how many there were
+pub async fn validate(crunch_file: &Path) -> anyhow::Result<usize> {
+    let content = tokio::fs::read_to_string(crunch_file)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to load config: {}: {}", crunch_file.display(), e))?;
+
+    let diagnostics = match crunch_file::File::parse(&content).await {
+        Ok(file) => file.validate(&config::crunch_dir(crunch_file)),
+        Err(e) => vec![Diagnostic::from_parse_error(&e)],
+    };
+
+    for diagnostic in &diagnostics {
+        let report = Report::new(report_diagnostic(diagnostic)).with_source_code(NamedSource::new(
+            crunch_file.display().to_string(),
+            content.clone(),
+        ));
+        eprintln!("{:?}", report);
+    }
+
+    Ok(diagnostics.len())
+}
+
+fn report_diagnostic(diagnostic: &Diagnostic) -> MietteDiagnostic {
+    let mut report = MietteDiagnostic::new(&diagnostic.message)
+        .with_severity(Severity::Error)
+        .with_labels(
+            diagnostic
+                .labels
+                .iter()
+                .map(|l| LabeledSpan::at(l.span.clone(), &l.message)),
+        );
+    if let Some(help) = &diagnostic.help {
+        report = report.with_help(help);
+    }
+
+    report
+}
@@ -0,0 +1,100 @@
+use std::{path::Path, time::Duration};
+
+use crunch_codegen::Target;
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+
+use crate::{config, generate};
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Regenerates targets whenever their schemas change, and everything when the crunch file changes.
+///
+/// Failures are printed and watching continues, such that a broken schema can be fixed in place.
+pub async fn watch(crunch_file: &Path) -> anyhow::Result<()> {
+    let crunch_dir = config::crunch_dir(crunch_file);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<DebounceEventResult>();
+
+    loop {
+        let mut debouncer = new_debouncer(DEBOUNCE, {
+            let tx = tx.clone();
+            move |res| {
+                let _ = tx.send(res);
+            }
+        })?;
+        // Editors commonly replace the file on save, which a watch on the file itself wouldn't survive
+        let crunch_file_path = crunch_file.canonicalize()?;
+        if let Some(dir) = crunch_file_path.parent() {
+            debouncer
+                .watcher()
+                .watch(dir, RecursiveMode::NonRecursive)?;
+        }
+
+        let (languages, targets) = match load_targets(crunch_file).await {
+            Ok(targets) => targets,
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                (Vec::new(), Vec::new())
+            }
+        };
+
+        let mut watched = Vec::new();
+        for target in targets {
+            let schema_path = crunch_dir.join(&target.schema_path);
+            match schema_path.canonicalize() {
+                Ok(path) => {
+                    debouncer.watcher().watch(&path, RecursiveMode::Recursive)?;
+                    generate_target(&crunch_dir, &languages, &target).await;
+                    watched.push((path, target));
+                }
+                Err(e) => eprintln!(
+                    "error: cannot watch schemas at: {}, {}",
+                    schema_path.display(),
+                    e
+                ),
+            }
+        }
+
+        println!("watching for changes, press ctrl-c to stop");
+        while let Some(res) = rx.recv().await {
+            let changed = match res {
+                Ok(events) => events.into_iter().map(|e| e.path).collect::<Vec<_>>(),
+                Err(e) => {
+                    eprintln!("error: watching failed: {}", e);
+                    continue;
+                }
+            };
+
+            if changed.iter().any(|p| is_same_file(p, &crunch_file_path)) {
+                println!("crunch file changed, reloading");
+                break;
+            }
+
+            for (path, target) in &watched {
+                if changed.iter().any(|p| p.starts_with(path)) {
+                    generate_target(&crunch_dir, &languages, target).await;
+                }
+            }
+        }
+    }
+}
+
+async fn load_targets(crunch_file: &Path) -> anyhow::Result<(Vec<String>, Vec<Target>)> {
+    let config = config::get_config(crunch_file).await?;
+    let targets = crunch_codegen::targets(&config)?;
+
+    Ok((config.service.codegen, targets))
+}
+
+async fn generate_target(crunch_dir: &Path, languages: &[String], target: &Target) {
+    if let Err(e) = generate::generate(crunch_dir, languages, target).await {
+        eprintln!(
+            "error: generating {} failed: {:#}",
+            target.schema_path.display(),
+            e
+        );
+    }
+}
+
+fn is_same_file(path: &Path, canonical: &Path) -> bool {
+    path == canonical || path.canonicalize().map(|p| p == canonical).unwrap_or(false)
+}
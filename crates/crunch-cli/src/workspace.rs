@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+
+use crunch_file::{Config, Publish, Workspace, WORKSPACE_FILE};
+
+use crate::config;
+
+/// A crunch file and the service it describes
+pub struct Member {
+    pub crunch_file: PathBuf,
+    pub crunch_dir: PathBuf,
+    pub config: Config,
+}
+
+impl Member {
+    pub async fn load(crunch_file: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            crunch_file: crunch_file.to_path_buf(),
+            crunch_dir: config::crunch_dir(crunch_file),
+            config: config::get_config(crunch_file).await?,
+        })
+    }
+}
+
+/// The members listed in the `crunch-workspace.toml` at `root`, or every crunch file found below it.
+///
+/// Subscriptions to another member, without a `schema-path` of their own, point at the schemas of that member, such
+/// that services in the same repository don't need a registry to share their events. They use the working copy of the
+/// schemas, whatever version the subscription asks for
+pub async fn members(root: &Path) -> anyhow::Result<Vec<Member>> {
+    let mut members = Vec::new();
+    for crunch_file in crunch_files(root).await? {
+        let member = Member::load(&crunch_file)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}: {}", crunch_file.display(), e))?;
+        members.push(member);
+    }
+
+    resolve_local_subscriptions(&mut members)?;
+
+    Ok(members)
+}
+
+/// Crunch files listed in the `crunch-workspace.toml` at `root`, or found below it
+pub async fn crunch_files(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let workspace_file = root.join(WORKSPACE_FILE);
+    let crunch_files = if workspace_file.exists() {
+        Workspace::parse_file(&workspace_file)
+            .await
+            .map_err(|e| anyhow::anyhow!("invalid workspace: {}: {}", workspace_file.display(), e))?
+            .crunch_files()
+            .into_iter()
+            .map(|crunch_file| root.join(crunch_file))
+            .collect()
+    } else {
+        find_crunch_files(root)?
+    };
+
+    Ok(crunch_files)
+}
+
+fn resolve_local_subscriptions(members: &mut [Member]) -> anyhow::Result<()> {
+    let mut published: HashMap<(String, String), (PathBuf, Vec<Publish>)> = HashMap::new();
+    for member in members.iter() {
+        let service = (
+            member.config.service.domain.clone(),
+            member.config.service.service.clone(),
+        );
+        if published.contains_key(&service) {
+            anyhow::bail!(
+                "service: {}/{} is defined by more than one crunch file, the second is: {}",
+                service.0,
+                service.1,
+                member.crunch_file.display()
+            );
+        }
+
+        let publish = member.config.publish.clone().unwrap_or_default();
+        published.insert(service, (member.crunch_dir.clone(), publish));
+    }
+
+    for member in members.iter_mut() {
+        for subscription in member.config.subscription.iter_mut().flatten() {
+            if subscription.schema_path.is_some() {
+                continue;
+            }
+            let Some((publisher_dir, publish)) =
+                published.get(&(subscription.domain.clone(), subscription.service.clone()))
+            else {
+                continue;
+            };
+
+            let publish = match publish.as_slice() {
+                [publish] => publish,
+                [] => anyhow::bail!(
+                    "{}: subscription: {}/{} is part of the workspace, but publishes no schemas",
+                    member.crunch_file.display(),
+                    subscription.domain,
+                    subscription.service
+                ),
+                _ => anyhow::bail!(
+                    "{}: subscription: {}/{} has several [[publish]] entries, set schema-path to pick one",
+                    member.crunch_file.display(),
+                    subscription.domain,
+                    subscription.service
+                ),
+            };
+
+            let schema_path = publisher_dir.join(&publish.schema_path);
+            let relative = relative_path(&schema_path, &member.crunch_dir).map_err(|e| {
+                anyhow::anyhow!(
+                    "{}: cannot resolve schemas of subscription: {}/{} at: {}, {}",
+                    member.crunch_file.display(),
+                    subscription.domain,
+                    subscription.service,
+                    schema_path.display(),
+                    e
+                )
+            })?;
+            tracing::debug!(
+                "resolved subscription: {}/{} to: {}",
+                subscription.domain,
+                subscription.service,
+                relative.display()
+            );
+            subscription.schema_path = Some(relative.display().to_string());
+            if subscription.event_pattern.is_none() {
+                subscription.event_pattern = publish.event_pattern.clone();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `path` relative to the directory `base`, both have to exist
+fn relative_path(path: &Path, base: &Path) -> anyhow::Result<PathBuf> {
+    let path = path.canonicalize()?;
+    let base = base_dir(base).canonicalize()?;
+
+    let path_components = path.components().collect::<Vec<_>>();
+    let base_components = base.components().collect::<Vec<_>>();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base_components.len() {
+        relative.push(Component::ParentDir);
+    }
+    for component in &path_components[common..] {
+        relative.push(component);
+    }
+    if relative.as_os_str().is_empty() {
+        relative.push(Component::CurDir);
+    }
+
+    Ok(relative)
+}
+
+/// The crunch dir of a crunch file in the current directory is empty
+fn base_dir(dir: &Path) -> &Path {
+    if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }
+}
+
+fn find_crunch_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut crunch_files = Vec::new();
+    let entries = walkdir::WalkDir::new(dir).into_iter().filter_entry(|e| {
+        !(e.file_type().is_dir()
+            && matches!(
+                e.file_name().to_str(),
+                Some("target" | ".git" | "node_modules" | ".crunch")
+            ))
+    });
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type().is_file() && entry.file_name() == ".crunch.toml" {
+            crunch_files.push(entry.into_path());
+        }
+    }
+
+    if crunch_files.is_empty() {
+        anyhow::bail!("no .crunch.toml files found in: {}", dir.display());
+    }
+    crunch_files.sort();
+
+    Ok(crunch_files)
+}
@@ -0,0 +1,188 @@
+use std::{
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+fn crunch(dir: &Path, args: &[&str]) -> anyhow::Result<Output> {
+    let output = Command::new(env!("CARGO_BIN_EXE_crunch"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()?;
+
+    Ok(output)
+}
+
+#[test]
+fn test_init_from_flags() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &[
+            "init",
+            "--service",
+            "my-service",
+            "--domain",
+            "my-domain",
+            "--codegen",
+            "rust,go",
+        ],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let content = std::fs::read_to_string(dir.path().join(".crunch.toml"))?;
+    pretty_assertions::assert_eq!(
+        r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust", "go"]
+"#,
+        content
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_init_defaults_codegen_to_rust() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &[
+            "init",
+            "--yes",
+            "--service",
+            "my-service",
+            "--domain",
+            "my-domain",
+        ],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let content = std::fs::read_to_string(dir.path().join(".crunch.toml"))?;
+    assert!(content.contains(r#"codegen = ["rust"]"#));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_fails_on_missing_flag() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(dir.path(), &["init", "--yes", "--service", "my-service"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("missing --domain"));
+    assert!(!dir.path().join(".crunch.toml").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_init_fails_on_invalid_name() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &["init", "--service", "My Service", "--domain", "my-domain"],
+    )?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("invalid --service"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_publish_from_flags() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &["init", "--service", "my-service", "--domain", "my-domain"],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = crunch(
+        dir.path(),
+        &[
+            "init",
+            "publish",
+            "--yes",
+            "--schema-path",
+            "schemas",
+            "--output-path",
+            "src/events",
+            "--entity",
+            "my-entity",
+        ],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let content = std::fs::read_to_string(dir.path().join(".crunch.toml"))?;
+    assert!(content.contains(r#"schema-path = "schemas""#));
+    assert!(content.contains(r#"output-path = "src/events""#));
+    assert!(content.contains(r#"entities = ["my-entity"]"#));
+
+    let schema = std::fs::read_to_string(dir.path().join("schemas/my-entity.proto"))?;
+    assert!(schema.contains("package my_domain.my_entity;"));
+    // Without the option crunch generate wouldn't find the scaffolded event
+    assert!(schema.contains(r#"import "crunch/options.proto";"#));
+    assert!(schema.contains("option (crunch.event) = true;"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_publish_fails_on_missing_entity() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &["init", "--service", "my-service", "--domain", "my-domain"],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = crunch(dir.path(), &["init", "publish", "--yes"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("missing --entity"));
+
+    let content = std::fs::read_to_string(dir.path().join(".crunch.toml"))?;
+    assert!(!content.contains("schema-path"));
+
+    Ok(())
+}
+
+#[test]
+fn test_init_subscribe_defaults() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    let output = crunch(
+        dir.path(),
+        &["init", "--service", "my-service", "--domain", "my-domain"],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = crunch(
+        dir.path(),
+        &[
+            "init",
+            "subscribe",
+            "--service",
+            "other-service",
+            "--domain",
+            "other-domain",
+        ],
+    )?;
+    assert!(output.status.success(), "{:?}", output);
+
+    let content = std::fs::read_to_string(dir.path().join(".crunch.toml"))?;
+    assert!(content.contains(r#"version = "1.0.0""#));
+    assert!(content.contains(r#"output-path = "src/gencrunch_other_service""#));
+
+    let output = crunch(dir.path(), &["init", "subscribe", "--service", "third"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("missing --domain"));
+
+    Ok(())
+}
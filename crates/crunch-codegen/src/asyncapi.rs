@@ -0,0 +1,366 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto,
+};
+use serde_json::{json, Map, Value};
+
+use crate::describe::{qualify, Comments, SchemaDescription};
+
+const ASYNCAPI_VERSION: &str = "2.6.0";
+const NATS_BINDING_VERSION: &str = "0.1.0";
+
+/// Builds an AsyncAPI document of the events a service publishes, with a channel per subject
+pub fn asyncapi(
+    service: &crunch_file::Service,
+    nats_url: &str,
+    descriptions: &[SchemaDescription],
+) -> Value {
+    let types = Types::new(descriptions);
+    let mut comments = Comments::default();
+    for description in descriptions {
+        comments.extend(Comments::new(&description.file_descriptor_set));
+    }
+
+    let mut channels = Map::new();
+    let mut messages = Map::new();
+    let mut referenced = Vec::new();
+    for event in descriptions.iter().flat_map(|d| &d.events) {
+        let mut message = json!({
+            "name": event.event_name,
+            "title": event.event_name,
+            "contentType": "application/x-protobuf",
+            "payload": schema_ref(&event.message),
+        });
+        if let Some(comment) = comments.message(&event.message) {
+            message["summary"] = json!(comment);
+        }
+        messages.insert(event.message.clone(), message);
+        referenced.push(event.message.clone());
+
+        // In AsyncAPI 2.x `subscribe` describes what others can subscribe to, i.e. what the service publishes
+        channels.insert(
+            event.subject(),
+            json!({
+                "subscribe": {
+                    "operationId": event.message,
+                    "message": { "$ref": format!("#/components/messages/{}", event.message) },
+                    "bindings": { "nats": { "bindingVersion": NATS_BINDING_VERSION } },
+                },
+            }),
+        );
+    }
+
+    let mut info = json!({
+        "title": service.service,
+        "version": service.version.as_deref().unwrap_or("0.0.0"),
+        "description": format!(
+            "Domain events published by {} in the {} domain",
+            service.service, service.domain
+        ),
+    });
+    if service.version.is_none() {
+        info["x-unversioned"] = json!(true);
+    }
+
+    json!({
+        "asyncapi": ASYNCAPI_VERSION,
+        "id": format!("urn:crunch:{}:{}", service.domain, service.service),
+        "info": info,
+        "defaultContentType": "application/x-protobuf",
+        "servers": {
+            "nats": {
+                "url": nats_url,
+                "protocol": "nats",
+                // The nats server binding is reserved and has no properties yet
+                "bindings": { "nats": {} },
+            },
+        },
+        "channels": channels,
+        "components": {
+            "messages": messages,
+            "schemas": types.schemas(referenced, &comments),
+        },
+    })
+}
+
+/// Messages and enums of all descriptor sets by their fully qualified name
+struct Types<'a> {
+    messages: HashMap<String, &'a DescriptorProto>,
+    enums: HashMap<String, &'a EnumDescriptorProto>,
+}
+
+impl<'a> Types<'a> {
+    fn new(descriptions: &'a [SchemaDescription]) -> Self {
+        let mut types = Self {
+            messages: HashMap::new(),
+            enums: HashMap::new(),
+        };
+        for file in descriptions
+            .iter()
+            .flat_map(|d| &d.file_descriptor_set.file)
+        {
+            for message in &file.message_type {
+                types.insert_message(file.package(), message);
+            }
+            for enum_type in &file.enum_type {
+                types
+                    .enums
+                    .insert(qualify(file.package(), enum_type.name()), enum_type);
+            }
+        }
+
+        types
+    }
+
+    fn insert_message(&mut self, scope: &str, message: &'a DescriptorProto) {
+        let name = qualify(scope, message.name());
+        for nested in &message.nested_type {
+            self.insert_message(&name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.enums
+                .insert(qualify(&name, enum_type.name()), enum_type);
+        }
+        self.messages.insert(name, message);
+    }
+
+    /// JSON schemas of the messages and every message they reference
+    fn schemas(&self, messages: Vec<String>, comments: &Comments) -> Map<String, Value> {
+        let mut schemas = BTreeMap::new();
+        let mut queue = VecDeque::from(messages);
+        let mut seen = BTreeSet::new();
+        while let Some(name) = queue.pop_front() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let Some(message) = self.messages.get(&name) else {
+                continue;
+            };
+
+            let mut properties = Map::new();
+            for field in &message.field {
+                let mut schema = self.field_schema(field, &mut queue);
+                if let Some(comment) = comments.field(&name, field.name()) {
+                    schema["description"] = json!(comment);
+                }
+                properties.insert(field.name().to_string(), schema);
+            }
+
+            let mut schema = json!({
+                "type": "object",
+                "properties": properties,
+            });
+            if let Some(comment) = comments.message(&name) {
+                schema["description"] = json!(comment);
+            }
+            schemas.insert(name, schema);
+        }
+
+        schemas.into_iter().collect()
+    }
+
+    fn field_schema(&self, field: &FieldDescriptorProto, queue: &mut VecDeque<String>) -> Value {
+        let type_name = field.type_name().trim_start_matches('.');
+
+        // Maps are repeated entries of a generated message with a key and value field
+        if let Some(entry) = self
+            .messages
+            .get(type_name)
+            .filter(|m| m.options.as_ref().and_then(|o| o.map_entry) == Some(true))
+        {
+            let value = entry
+                .field
+                .iter()
+                .find(|f| f.number() == 2)
+                .map(|f| self.field_schema(f, queue))
+                .unwrap_or(json!({}));
+            return json!({
+                "type": "object",
+                "additionalProperties": value,
+            });
+        }
+
+        let schema = match field.r#type() {
+            Type::Double | Type::Float => json!({ "type": "number" }),
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => {
+                json!({ "type": "integer", "format": "int32" })
+            }
+            Type::Uint32 | Type::Fixed32 => json!({ "type": "integer", "format": "uint32" }),
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+                json!({ "type": "integer", "format": "int64" })
+            }
+            Type::Uint64 | Type::Fixed64 => json!({ "type": "integer", "format": "uint64" }),
+            Type::Bool => json!({ "type": "boolean" }),
+            Type::String => json!({ "type": "string" }),
+            Type::Bytes => json!({ "type": "string", "format": "byte" }),
+            Type::Enum => match self.enums.get(type_name) {
+                Some(enum_type) => json!({
+                    "type": "string",
+                    "enum": enum_type.value.iter().map(|v| v.name()).collect::<Vec<_>>(),
+                }),
+                None => json!({ "type": "string" }),
+            },
+            Type::Message | Type::Group => {
+                queue.push_back(type_name.to_string());
+                schema_ref(type_name)
+            }
+        };
+
+        if field.label() == Label::Repeated {
+            json!({ "type": "array", "items": schema })
+        } else {
+            schema
+        }
+    }
+}
+
+fn schema_ref(message: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", message) })
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{FileDescriptorProto, FileDescriptorSet, MessageOptions};
+
+    use super::*;
+    use crate::EventDescription;
+
+    fn field(
+        name: &str,
+        number: i32,
+        field_type: Type,
+        type_name: Option<&str>,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.into()),
+            number: Some(number),
+            r#type: Some(field_type as i32),
+            type_name: type_name.map(|t| t.into()),
+            label: Some(Label::Optional as i32),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_asyncapi() {
+        let mut tags = field(
+            "tags",
+            3,
+            Type::Message,
+            Some(".users.user.UserCreated.TagsEntry"),
+        );
+        tags.label = Some(Label::Repeated as i32);
+
+        let description = SchemaDescription {
+            file_descriptor_set: FileDescriptorSet {
+                file: vec![FileDescriptorProto {
+                    name: Some("user.proto".into()),
+                    package: Some("users.user".into()),
+                    message_type: vec![
+                        DescriptorProto {
+                            name: Some("UserCreated".into()),
+                            field: vec![
+                                field("user_id", 1, Type::String, None),
+                                field("address", 2, Type::Message, Some(".users.user.Address")),
+                                tags,
+                            ],
+                            nested_type: vec![DescriptorProto {
+                                name: Some("TagsEntry".into()),
+                                field: vec![
+                                    field("key", 1, Type::String, None),
+                                    field("value", 2, Type::Int64, None),
+                                ],
+                                options: Some(MessageOptions {
+                                    map_entry: Some(true),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            }],
+                            ..Default::default()
+                        },
+                        DescriptorProto {
+                            name: Some("Address".into()),
+                            field: vec![field("city", 1, Type::String, None)],
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                }],
+            },
+            events: vec![EventDescription {
+                domain: "users".into(),
+                entity_type: "user".into(),
+                event_name: "UserCreated".into(),
+                message: "users.user.UserCreated".into(),
+                file: "user.proto".into(),
+            }],
+        };
+        let service = crunch_file::Service {
+            service: "users-service".into(),
+            domain: "users".into(),
+            codegen: vec!["rust".into()],
+            version: Some("1.2.0".into()),
+        };
+
+        let document = asyncapi(&service, "nats://localhost:4222", &[description]);
+
+        pretty_assertions::assert_eq!(
+            document,
+            json!({
+                "asyncapi": "2.6.0",
+                "id": "urn:crunch:users:users-service",
+                "info": {
+                    "title": "users-service",
+                    "version": "1.2.0",
+                    "description": "Domain events published by users-service in the users domain",
+                },
+                "defaultContentType": "application/x-protobuf",
+                "servers": {
+                    "nats": {
+                        "url": "nats://localhost:4222",
+                        "protocol": "nats",
+                        "bindings": { "nats": {} },
+                    },
+                },
+                "channels": {
+                    "crunch.users.user.UserCreated": {
+                        "subscribe": {
+                            "operationId": "users.user.UserCreated",
+                            "message": { "$ref": "#/components/messages/users.user.UserCreated" },
+                            "bindings": { "nats": { "bindingVersion": "0.1.0" } },
+                        },
+                    },
+                },
+                "components": {
+                    "messages": {
+                        "users.user.UserCreated": {
+                            "name": "UserCreated",
+                            "title": "UserCreated",
+                            "contentType": "application/x-protobuf",
+                            "payload": { "$ref": "#/components/schemas/users.user.UserCreated" },
+                        },
+                    },
+                    "schemas": {
+                        "users.user.Address": {
+                            "type": "object",
+                            "properties": { "city": { "type": "string" } },
+                        },
+                        "users.user.UserCreated": {
+                            "type": "object",
+                            "properties": {
+                                "user_id": { "type": "string" },
+                                "address": { "$ref": "#/components/schemas/users.user.Address" },
+                                "tags": {
+                                    "type": "object",
+                                    "additionalProperties": { "type": "integer", "format": "int64" },
+                                },
+                            },
+                        },
+                    },
+                },
+            })
+        );
+    }
+}
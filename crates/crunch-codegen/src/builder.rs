@@ -0,0 +1,147 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use walkdir::WalkDir;
+
+/// Generates the rust code described by a crunch file from a `build.rs`, into `OUT_DIR`.
+///
+/// Every `[[publish]]` and `[[subscription]]` becomes a module named after the last segment of its `output-path`,
+/// include them all with `crunch::include_events!()`.
+///
+/// ```no_run
+/// // build.rs
+/// fn main() -> anyhow::Result<()> {
+///     crunch_codegen::Builder::new().build()
+/// }
+/// ```
+pub struct Builder {
+    crunch_file: PathBuf,
+    out_dir: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            crunch_file: PathBuf::from(".crunch.toml"),
+            out_dir: None,
+        }
+    }
+
+    /// Path of the crunch file, relative to the package root, defaults to `.crunch.toml`
+    pub fn with_crunch_file(&mut self, crunch_file: impl Into<PathBuf>) -> &mut Self {
+        self.crunch_file = crunch_file.into();
+        self
+    }
+
+    /// Directory to generate into, defaults to `OUT_DIR` as set by cargo
+    pub fn with_out_dir(&mut self, out_dir: impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    pub fn build(&self) -> anyhow::Result<()> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(self.generate())
+    }
+
+    async fn generate(&self) -> anyhow::Result<()> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => PathBuf::from(std::env::var("OUT_DIR").context("OUT_DIR is not set")?),
+        }
+        .join("crunch");
+
+        println!("cargo:rerun-if-changed={}", self.crunch_file.display());
+        println!("cargo:rerun-if-env-changed=PROTOC");
+
+        let config = crunch_file::File::parse_file(&self.crunch_file)
+            .await
+            .with_context(|| format!("failed to load: {}", self.crunch_file.display()))?
+            .get_config()?;
+        let crunch_dir = self
+            .crunch_file
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+
+        let mut modules = BTreeSet::new();
+        for target in crate::targets(&config)? {
+            let schema_path = crunch_dir.join(&target.schema_path);
+            if !schema_path.exists() {
+                anyhow::bail!(
+                    "schemas not found at: {}, subscriptions to a git registry have to be fetched with: crunch schema fetch",
+                    schema_path.display()
+                );
+            }
+            rerun_if_schemas_changed(&schema_path)?;
+
+            let module = module_name(&target.output_path)?;
+            if !modules.insert(module.clone()) {
+                anyhow::bail!(
+                    "output path: {} ends in the same module name as another output path",
+                    target.output_path
+                );
+            }
+
+            target
+                .codegen()
+                .generate_rust(&schema_path, &out_dir.join(&module))
+                .await?;
+        }
+
+        // Modules of removed publish and subscription entries
+        if out_dir.exists() {
+            let mut entries = tokio::fs::read_dir(&out_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.file_type().await?.is_dir() && !modules.contains(&name) {
+                    tokio::fs::remove_dir_all(entry.path()).await?;
+                }
+            }
+        }
+
+        let mod_contents = modules
+            .iter()
+            .map(|m| format!("pub mod {m} {{\n    include!(\"{m}/mod.rs\");\n}}\n"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        tokio::fs::create_dir_all(&out_dir).await?;
+        tokio::fs::write(out_dir.join("mod.rs"), mod_contents).await?;
+
+        Ok(())
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn rerun_if_schemas_changed(schema_path: &Path) -> anyhow::Result<()> {
+    // The directory catches added schemas, the files themselves catch edits
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+    for entry in WalkDir::new(schema_path) {
+        let entry = entry?;
+        if entry.path().extension().and_then(|e| e.to_str()) == Some("proto") {
+            println!("cargo:rerun-if-changed={}", entry.path().display());
+        }
+    }
+
+    Ok(())
+}
+
+/// `src/gencrunch_users` becomes `gencrunch_users`
+fn module_name(output_path: &str) -> anyhow::Result<String> {
+    let name = Path::new(output_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or(anyhow::anyhow!("output path: {} has no name", output_path))?;
+
+    Ok(name.replace('-', "_"))
+}
@@ -0,0 +1,378 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use prost_types::{field_descriptor_proto::Label, DescriptorProto};
+
+use crate::{
+    compat::field_type,
+    describe::{qualify, Comments, SchemaDescription},
+    EventDescription,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Markdown,
+    Html,
+}
+
+/// An event as shown in the catalog, with the services producing and consuming it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogEvent {
+    pub event: EventDescription,
+    pub description: Option<String>,
+    pub producer: String,
+    pub consumers: Vec<String>,
+    pub fields: Vec<CatalogField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogField {
+    pub name: String,
+    pub number: i32,
+    pub field_type: String,
+    pub description: Option<String>,
+}
+
+/// Catalog entries of the events in `description`, all produced by `producer`
+pub fn catalog_events(
+    description: &SchemaDescription,
+    producer: &str,
+    consumers: &[String],
+) -> Vec<CatalogEvent> {
+    let comments = Comments::new(&description.file_descriptor_set);
+    let mut messages = HashMap::new();
+    for file in &description.file_descriptor_set.file {
+        for message in &file.message_type {
+            collect_messages(file.package(), message, &mut messages);
+        }
+    }
+
+    description
+        .events
+        .iter()
+        .map(|event| {
+            let fields = messages
+                .get(&event.message)
+                .map(|message| {
+                    message
+                        .field
+                        .iter()
+                        .map(|field| CatalogField {
+                            name: field.name().to_string(),
+                            number: field.number(),
+                            field_type: map_type(field, &messages)
+                                .unwrap_or_else(|| field_type(field)),
+                            description: comments
+                                .field(&event.message, field.name())
+                                .map(|c| c.to_string()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            CatalogEvent {
+                event: event.clone(),
+                description: comments.message(&event.message).map(|c| c.to_string()),
+                producer: producer.to_string(),
+                consumers: consumers.to_vec(),
+                fields,
+            }
+        })
+        .collect()
+}
+
+/// An event and the link to its page
+type IndexEntry<'a> = (&'a CatalogEvent, String);
+
+/// Renders a page per event at `{domain}/{entity}/{event}`, and an index linking them all
+pub fn render_catalog(events: &[CatalogEvent], format: CatalogFormat) -> BTreeMap<PathBuf, String> {
+    let extension = match format {
+        CatalogFormat::Markdown => "md",
+        CatalogFormat::Html => "html",
+    };
+
+    let mut pages = BTreeMap::new();
+    let mut index: BTreeMap<&str, BTreeMap<&str, Vec<IndexEntry>>> = BTreeMap::new();
+    for event in events {
+        let link = format!(
+            "{}/{}/{}.{}",
+            event.event.domain, event.event.entity_type, event.event.event_name, extension
+        );
+        let page = match format {
+            CatalogFormat::Markdown => markdown_page(event),
+            CatalogFormat::Html => html_page(event),
+        };
+        pages.insert(PathBuf::from(&link), page);
+
+        index
+            .entry(&event.event.domain)
+            .or_default()
+            .entry(&event.event.entity_type)
+            .or_default()
+            .push((event, link));
+    }
+
+    let index_page = match format {
+        CatalogFormat::Markdown => {
+            let mut page = "# Event catalog\n".to_string();
+            for (domain, entities) in &index {
+                page.push_str(&format!("\n## {}\n", domain));
+                for (entity, events) in entities {
+                    page.push_str(&format!("\n### {}\n\n", entity));
+                    for (event, link) in events {
+                        page.push_str(&format!(
+                            "- [{}]({}), produced by {}\n",
+                            event.event.event_name, link, event.producer
+                        ));
+                    }
+                }
+            }
+            page
+        }
+        CatalogFormat::Html => {
+            let mut body = "<h1>Event catalog</h1>\n".to_string();
+            for (domain, entities) in &index {
+                body.push_str(&format!("<h2>{}</h2>\n", escape_html(domain)));
+                for (entity, events) in entities {
+                    body.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape_html(entity)));
+                    for (event, link) in events {
+                        body.push_str(&format!(
+                            "<li><a href=\"{}\">{}</a>, produced by {}</li>\n",
+                            escape_html(link),
+                            escape_html(&event.event.event_name),
+                            escape_html(&event.producer)
+                        ));
+                    }
+                    body.push_str("</ul>\n");
+                }
+            }
+            html_document("Event catalog", &body)
+        }
+    };
+    pages.insert(PathBuf::from(format!("index.{}", extension)), index_page);
+
+    pages
+}
+
+fn markdown_page(event: &CatalogEvent) -> String {
+    let mut page = format!("# {}\n\n", event.event.event_name);
+    if let Some(description) = &event.description {
+        page.push_str(&format!("{}\n\n", description));
+    }
+
+    page.push_str("| | |\n|---|---|\n");
+    for (key, value) in overview(event) {
+        page.push_str(&format!("| {} | {} |\n", key, escape_markdown(&value)));
+    }
+
+    page.push_str("\n## Fields\n\n| Field | Number | Type | Description |\n|---|---|---|---|\n");
+    for field in &event.fields {
+        page.push_str(&format!(
+            "| {} | {} | `{}` | {} |\n",
+            field.name,
+            field.number,
+            field.field_type,
+            escape_markdown(field.description.as_deref().unwrap_or_default())
+        ));
+    }
+
+    page
+}
+
+fn html_page(event: &CatalogEvent) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape_html(&event.event.event_name));
+    if let Some(description) = &event.description {
+        body.push_str(&format!("<p>{}</p>\n", escape_html(description)));
+    }
+
+    body.push_str("<table>\n");
+    for (key, value) in overview(event) {
+        body.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            key,
+            escape_html(&value)
+        ));
+    }
+    body.push_str("</table>\n<h2>Fields</h2>\n<table>\n<tr><th>Field</th><th>Number</th><th>Type</th><th>Description</th></tr>\n");
+    for field in &event.fields {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+            escape_html(&field.name),
+            field.number,
+            escape_html(&field.field_type),
+            escape_html(field.description.as_deref().unwrap_or_default())
+        ));
+    }
+    body.push_str("</table>\n");
+
+    html_document(&event.event.event_name, &body)
+}
+
+fn overview(event: &CatalogEvent) -> Vec<(&'static str, String)> {
+    vec![
+        ("Domain", event.event.domain.clone()),
+        ("Entity", event.event.entity_type.clone()),
+        ("Subject", event.event.subject()),
+        ("Message", event.event.message.clone()),
+        ("Produced by", event.producer.clone()),
+        (
+            "Consumed by",
+            if event.consumers.is_empty() {
+                "no known consumers".into()
+            } else {
+                event.consumers.join(", ")
+            },
+        ),
+    ]
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Table cells can't contain pipes or line breaks
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn collect_messages<'a>(
+    scope: &str,
+    message: &'a DescriptorProto,
+    messages: &mut HashMap<String, &'a DescriptorProto>,
+) {
+    let name = qualify(scope, message.name());
+    for nested in &message.nested_type {
+        collect_messages(&name, nested, messages);
+    }
+    messages.insert(name, message);
+}
+
+/// `map<string, int64>` instead of the repeated entry message protoc generates for it
+fn map_type(
+    field: &prost_types::FieldDescriptorProto,
+    messages: &HashMap<String, &DescriptorProto>,
+) -> Option<String> {
+    if field.label() != Label::Repeated {
+        return None;
+    }
+
+    let entry = messages
+        .get(field.type_name().trim_start_matches('.'))
+        .filter(|m| m.options.as_ref().and_then(|o| o.map_entry) == Some(true))?;
+    let key = entry.field.iter().find(|f| f.number() == 1)?;
+    let value = entry.field.iter().find(|f| f.number() == 2)?;
+
+    Some(format!("map<{}, {}>", field_type(key), field_type(value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{
+        field_descriptor_proto::Type, source_code_info::Location, FieldDescriptorProto,
+        FileDescriptorProto, FileDescriptorSet, SourceCodeInfo,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_catalog() {
+        let description = SchemaDescription {
+            file_descriptor_set: FileDescriptorSet {
+                file: vec![FileDescriptorProto {
+                    name: Some("user.proto".into()),
+                    package: Some("users.user".into()),
+                    message_type: vec![DescriptorProto {
+                        name: Some("UserCreated".into()),
+                        field: vec![FieldDescriptorProto {
+                            name: Some("user_id".into()),
+                            number: Some(1),
+                            r#type: Some(Type::String as i32),
+                            label: Some(Label::Optional as i32),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    source_code_info: Some(SourceCodeInfo {
+                        location: vec![
+                            Location {
+                                path: vec![4, 0],
+                                leading_comments: Some(" A user signed up\n".into()),
+                                ..Default::default()
+                            },
+                            Location {
+                                path: vec![4, 0, 2, 0],
+                                leading_comments: Some(" Id of the user | uuid\n".into()),
+                                ..Default::default()
+                            },
+                        ],
+                    }),
+                    ..Default::default()
+                }],
+            },
+            events: vec![EventDescription {
+                domain: "users".into(),
+                entity_type: "user".into(),
+                event_name: "UserCreated".into(),
+                message: "users.user.UserCreated".into(),
+                file: "user.proto".into(),
+            }],
+        };
+
+        let events = catalog_events(&description, "users-service", &["onboarding".into()]);
+        let pages = render_catalog(&events, CatalogFormat::Markdown);
+
+        pretty_assertions::assert_eq!(
+            pages.keys().collect::<Vec<_>>(),
+            vec![
+                &PathBuf::from("index.md"),
+                &PathBuf::from("users/user/UserCreated.md")
+            ]
+        );
+        pretty_assertions::assert_eq!(
+            pages[&PathBuf::from("users/user/UserCreated.md")],
+            r#"# UserCreated
+
+A user signed up
+
+| | |
+|---|---|
+| Domain | users |
+| Entity | user |
+| Subject | crunch.users.user.UserCreated |
+| Message | users.user.UserCreated |
+| Produced by | users-service |
+| Consumed by | onboarding |
+
+## Fields
+
+| Field | Number | Type | Description |
+|---|---|---|---|
+| user_id | 1 | `string` | Id of the user \| uuid |
+"#
+        );
+        pretty_assertions::assert_eq!(
+            pages[&PathBuf::from("index.md")],
+            r#"# Event catalog
+
+## users
+
+### user
+
+- [UserCreated](users/user/UserCreated.md), produced by users-service
+"#
+        );
+    }
+}
@@ -0,0 +1,595 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet,
+};
+
+/// How a schema change affects existing publishers and subscribers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// Wire compatible, i.e. a field rename or a removed field whose number is reserved
+    Compatible,
+    /// A new message or field
+    Addition,
+    /// Old and new schemas can no longer read each others events
+    Breaking,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Compatible => write!(f, "compatible"),
+            ChangeKind::Addition => write!(f, "addition"),
+            ChangeKind::Breaking => write!(f, "breaking"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    /// Fully qualified name of the message or enum, i.e. `users.user.UserCreated`
+    pub message: String,
+    pub description: String,
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.kind, self.message, self.description)
+    }
+}
+
+/// Compares the messages and enums of two descriptor sets, the changes are ordered by message name
+pub fn compare(previous: &FileDescriptorSet, current: &FileDescriptorSet) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    compare_enums(&enums(previous), &enums(current), &mut changes);
+
+    let previous = messages(previous);
+    let current = messages(current);
+
+    let mut renamed = BTreeSet::new();
+    for (name, previous_message) in &previous {
+        match current.get(name) {
+            Some(current_message) => {
+                compare_fields(name, previous_message, current_message, &mut changes)
+            }
+            None => {
+                // A message with the exact same fields under a new name is most likely a rename
+                let rename = current.iter().find(|(current_name, current_message)| {
+                    !previous.contains_key(*current_name)
+                        && !renamed.contains(*current_name)
+                        && !previous_message.field.is_empty()
+                        && field_signature(current_message) == field_signature(previous_message)
+                });
+
+                let description = match rename {
+                    Some((current_name, _)) => {
+                        renamed.insert(current_name.clone());
+                        format!("message renamed to: {}", current_name)
+                    }
+                    None => "message removed".to_string(),
+                };
+                changes.push(SchemaChange {
+                    kind: ChangeKind::Breaking,
+                    message: name.clone(),
+                    description,
+                });
+            }
+        }
+    }
+
+    for name in current.keys() {
+        if !previous.contains_key(name) && !renamed.contains(name) {
+            changes.push(SchemaChange {
+                kind: ChangeKind::Addition,
+                message: name.clone(),
+                description: "message added".into(),
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| a.message.cmp(&b.message));
+    changes
+}
+
+fn compare_fields(
+    message: &str,
+    previous: &DescriptorProto,
+    current: &DescriptorProto,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let previous_fields = fields(previous);
+    let current_fields = fields(current);
+    let mut change = |kind, description| {
+        changes.push(SchemaChange {
+            kind,
+            message: message.to_string(),
+            description,
+        })
+    };
+
+    for (number, previous_field) in &previous_fields {
+        match current_fields.get(number) {
+            Some(current_field) => {
+                let previous_type = field_type(previous_field);
+                let current_type = field_type(current_field);
+                let renamed = previous_field.name() != current_field.name();
+
+                if previous_type != current_type && renamed {
+                    change(
+                        ChangeKind::Breaking,
+                        format!(
+                            "field number {} reused: {} ({}) is now {} ({})",
+                            number,
+                            previous_field.name(),
+                            previous_type,
+                            current_field.name(),
+                            current_type
+                        ),
+                    );
+                } else if previous_type != current_type {
+                    change(
+                        ChangeKind::Breaking,
+                        format!(
+                            "field {} ({}) changed type from {} to {}",
+                            current_field.name(),
+                            number,
+                            previous_type,
+                            current_type
+                        ),
+                    );
+                } else if renamed {
+                    change(
+                        ChangeKind::Compatible,
+                        format!(
+                            "field {} renamed from {} to {}",
+                            number,
+                            previous_field.name(),
+                            current_field.name()
+                        ),
+                    );
+                }
+            }
+            None if is_reserved(current, *number) => change(
+                ChangeKind::Compatible,
+                format!(
+                    "field {} ({}) removed, its number is reserved",
+                    previous_field.name(),
+                    number
+                ),
+            ),
+            None => change(
+                ChangeKind::Breaking,
+                format!(
+                    "field {} ({}) removed without reserving its number",
+                    previous_field.name(),
+                    number
+                ),
+            ),
+        }
+    }
+
+    for (number, current_field) in &current_fields {
+        if previous_fields.contains_key(number) {
+            continue;
+        }
+
+        if is_reserved(previous, *number) {
+            change(
+                ChangeKind::Breaking,
+                format!(
+                    "field {} reuses reserved field number {}",
+                    current_field.name(),
+                    number
+                ),
+            );
+        } else {
+            change(
+                ChangeKind::Addition,
+                format!("field {} ({}) added", current_field.name(), number),
+            );
+        }
+    }
+}
+
+fn compare_enums(
+    previous: &BTreeMap<String, &EnumDescriptorProto>,
+    current: &BTreeMap<String, &EnumDescriptorProto>,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for (name, previous_enum) in previous {
+        let mut change = |kind, description| {
+            changes.push(SchemaChange {
+                kind,
+                message: name.clone(),
+                description,
+            })
+        };
+
+        let Some(current_enum) = current.get(name) else {
+            change(ChangeKind::Breaking, "enum removed".into());
+            continue;
+        };
+
+        let previous_values = enum_values(previous_enum);
+        let current_values = enum_values(current_enum);
+        let current_numbers = current_values
+            .iter()
+            .map(|(name, number)| (*number, *name))
+            .collect::<BTreeMap<_, _>>();
+        let previous_numbers = previous_values
+            .iter()
+            .map(|(name, number)| (*number, *name))
+            .collect::<BTreeMap<_, _>>();
+
+        for (number, value) in &previous_numbers {
+            match (current_values.get(value), current_numbers.get(number)) {
+                (Some(current_number), _) if current_number != number => change(
+                    ChangeKind::Breaking,
+                    format!(
+                        "value {} renumbered from {} to {}",
+                        value, number, current_number
+                    ),
+                ),
+                (_, Some(current_value)) if current_value != value => change(
+                    ChangeKind::Compatible,
+                    format!(
+                        "value {} renamed from {} to {}",
+                        number, value, current_value
+                    ),
+                ),
+                (_, Some(_)) => {}
+                (_, None) if is_enum_reserved(current_enum, *number) => change(
+                    ChangeKind::Compatible,
+                    format!(
+                        "value {} ({}) removed, its number is reserved",
+                        value, number
+                    ),
+                ),
+                (_, None) => change(
+                    ChangeKind::Breaking,
+                    format!(
+                        "value {} ({}) removed without reserving its number",
+                        value, number
+                    ),
+                ),
+            }
+        }
+
+        for (number, value) in &current_numbers {
+            // Renumbered values are reported above
+            if previous_numbers.contains_key(number) || previous_values.contains_key(value) {
+                continue;
+            }
+
+            if is_enum_reserved(previous_enum, *number) {
+                change(
+                    ChangeKind::Breaking,
+                    format!("value {} reuses reserved number {}", value, number),
+                );
+            } else {
+                change(
+                    ChangeKind::Addition,
+                    format!("value {} ({}) added", value, number),
+                );
+            }
+        }
+    }
+
+    for name in current.keys() {
+        if !previous.contains_key(name) {
+            changes.push(SchemaChange {
+                kind: ChangeKind::Addition,
+                message: name.clone(),
+                description: "enum added".into(),
+            });
+        }
+    }
+}
+
+/// All enums, including those nested in messages, by their fully qualified name
+fn enums(set: &FileDescriptorSet) -> BTreeMap<String, &EnumDescriptorProto> {
+    fn collect<'a>(
+        prefix: &str,
+        message: &'a DescriptorProto,
+        enums: &mut BTreeMap<String, &'a EnumDescriptorProto>,
+    ) {
+        let name = qualify(prefix, message.name());
+        for enum_type in &message.enum_type {
+            enums.insert(qualify(&name, enum_type.name()), enum_type);
+        }
+        for nested in &message.nested_type {
+            collect(&name, nested, enums);
+        }
+    }
+
+    let mut enums = BTreeMap::new();
+    for file in &set.file {
+        for enum_type in &file.enum_type {
+            enums.insert(qualify(file.package(), enum_type.name()), enum_type);
+        }
+        for message in &file.message_type {
+            collect(file.package(), message, &mut enums);
+        }
+    }
+
+    enums
+}
+
+fn qualify(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn enum_values(enum_type: &EnumDescriptorProto) -> BTreeMap<&str, i32> {
+    enum_type
+        .value
+        .iter()
+        .map(|v| (v.name(), v.number()))
+        .collect()
+}
+
+fn is_enum_reserved(enum_type: &EnumDescriptorProto, number: i32) -> bool {
+    // Unlike those of messages, reserved ranges of enums are end inclusive
+    enum_type
+        .reserved_range
+        .iter()
+        .any(|r| r.start() <= number && number <= r.end())
+}
+
+/// All messages, including nested ones, by their fully qualified name
+fn messages(set: &FileDescriptorSet) -> BTreeMap<String, &DescriptorProto> {
+    fn collect<'a>(
+        prefix: &str,
+        message: &'a DescriptorProto,
+        messages: &mut BTreeMap<String, &'a DescriptorProto>,
+    ) {
+        let name = qualify(prefix, message.name());
+
+        for nested in &message.nested_type {
+            // Map entries are generated by protoc for `map<K, V>` fields, the field type covers them
+            if nested.options.as_ref().and_then(|o| o.map_entry) == Some(true) {
+                continue;
+            }
+            collect(&name, nested, messages);
+        }
+        messages.insert(name, message);
+    }
+
+    let mut messages = BTreeMap::new();
+    for file in &set.file {
+        for message in &file.message_type {
+            collect(file.package(), message, &mut messages);
+        }
+    }
+
+    messages
+}
+
+fn fields(message: &DescriptorProto) -> BTreeMap<i32, &FieldDescriptorProto> {
+    message.field.iter().map(|f| (f.number(), f)).collect()
+}
+
+fn field_signature(message: &DescriptorProto) -> Vec<(i32, String)> {
+    fields(message)
+        .into_iter()
+        .map(|(number, field)| (number, field_type(field)))
+        .collect()
+}
+
+pub(crate) fn field_type(field: &FieldDescriptorProto) -> String {
+    let field_type = match field.r#type() {
+        Type::Message | Type::Enum => field.type_name().trim_start_matches('.').to_string(),
+        scalar => scalar
+            .as_str_name()
+            .trim_start_matches("TYPE_")
+            .to_lowercase(),
+    };
+
+    if field.label() == Label::Repeated {
+        format!("repeated {}", field_type)
+    } else {
+        field_type
+    }
+}
+
+fn is_reserved(message: &DescriptorProto, number: i32) -> bool {
+    // Reserved ranges in descriptors are end exclusive
+    message
+        .reserved_range
+        .iter()
+        .any(|r| r.start() <= number && number < r.end())
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{
+        descriptor_proto::ReservedRange, enum_descriptor_proto::EnumReservedRange,
+        EnumValueDescriptorProto, FileDescriptorProto,
+    };
+
+    use super::*;
+
+    fn field(name: &str, number: i32, field_type: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.into()),
+            number: Some(number),
+            r#type: Some(field_type as i32),
+            label: Some(Label::Optional as i32),
+            ..Default::default()
+        }
+    }
+
+    fn message(name: &str, fields: Vec<FieldDescriptorProto>, reserved: &[i32]) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.into()),
+            field: fields,
+            reserved_range: reserved
+                .iter()
+                .map(|n| ReservedRange {
+                    start: Some(*n),
+                    end: Some(n + 1),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn set(messages: Vec<DescriptorProto>) -> FileDescriptorSet {
+        FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("user.proto".into()),
+                package: Some("users.user".into()),
+                message_type: messages,
+                ..Default::default()
+            }],
+        }
+    }
+
+    fn descriptions(changes: Vec<SchemaChange>) -> Vec<String> {
+        changes.into_iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare_fields() {
+        let previous = set(vec![message(
+            "UserCreated",
+            vec![
+                field("id", 1, Type::String),
+                field("name", 2, Type::String),
+                field("age", 3, Type::Int32),
+                field("email", 4, Type::String),
+                field("nickname", 5, Type::String),
+                field("legacy", 6, Type::String),
+            ],
+            &[7],
+        )]);
+        let current = set(vec![message(
+            "UserCreated",
+            vec![
+                field("id", 1, Type::String),
+                field("full_name", 2, Type::String),
+                field("age", 3, Type::Int64),
+                field("verified", 5, Type::Bool),
+                field("created", 7, Type::Int64),
+                field("country", 8, Type::String),
+            ],
+            &[6],
+        )]);
+
+        pretty_assertions::assert_eq!(
+            descriptions(compare(&previous, &current)),
+            vec![
+                "compatible: users.user.UserCreated: field 2 renamed from name to full_name",
+                "breaking: users.user.UserCreated: field age (3) changed type from int32 to int64",
+                "breaking: users.user.UserCreated: field email (4) removed without reserving its number",
+                "breaking: users.user.UserCreated: field number 5 reused: nickname (string) is now verified (bool)",
+                "compatible: users.user.UserCreated: field legacy (6) removed, its number is reserved",
+                "breaking: users.user.UserCreated: field created reuses reserved field number 7",
+                "addition: users.user.UserCreated: field country (8) added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_messages() {
+        let previous = set(vec![
+            message("UserCreated", vec![field("id", 1, Type::String)], &[]),
+            message("UserDeleted", vec![field("id", 1, Type::Int64)], &[]),
+        ]);
+        let current = set(vec![
+            message("UserRegistered", vec![field("id", 1, Type::String)], &[]),
+            message("UserUpdated", vec![field("id", 1, Type::String)], &[]),
+        ]);
+
+        pretty_assertions::assert_eq!(
+            descriptions(compare(&previous, &current)),
+            vec![
+                "breaking: users.user.UserCreated: message renamed to: users.user.UserRegistered",
+                "breaking: users.user.UserDeleted: message removed",
+                "addition: users.user.UserUpdated: message added",
+            ]
+        );
+    }
+
+    fn enum_set(values: &[(&str, i32)], reserved: &[i32]) -> FileDescriptorSet {
+        FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("user.proto".into()),
+                package: Some("users.user".into()),
+                enum_type: vec![EnumDescriptorProto {
+                    name: Some("Role".into()),
+                    value: values
+                        .iter()
+                        .map(|(name, number)| EnumValueDescriptorProto {
+                            name: Some(name.to_string()),
+                            number: Some(*number),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    reserved_range: reserved
+                        .iter()
+                        .map(|n| EnumReservedRange {
+                            start: Some(*n),
+                            end: Some(*n),
+                        })
+                        .collect(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn test_compare_enums() {
+        let previous = enum_set(
+            &[
+                ("ROLE_UNSPECIFIED", 0),
+                ("ROLE_ADMIN", 1),
+                ("ROLE_USER", 2),
+                ("ROLE_GUEST", 3),
+                ("ROLE_LEGACY", 4),
+                ("ROLE_OWNER", 5),
+            ],
+            &[6],
+        );
+        let current = enum_set(
+            &[
+                ("ROLE_UNSPECIFIED", 0),
+                ("ROLE_ADMINISTRATOR", 1),
+                ("ROLE_USER", 7),
+                ("ROLE_OWNER", 5),
+                ("ROLE_BOT", 6),
+                ("ROLE_SERVICE", 8),
+            ],
+            &[4],
+        );
+
+        pretty_assertions::assert_eq!(
+            descriptions(compare(&previous, &current)),
+            vec![
+                "compatible: users.user.Role: value 1 renamed from ROLE_ADMIN to ROLE_ADMINISTRATOR",
+                "breaking: users.user.Role: value ROLE_USER renumbered from 2 to 7",
+                "breaking: users.user.Role: value ROLE_GUEST (3) removed without reserving its number",
+                "compatible: users.user.Role: value ROLE_LEGACY (4) removed, its number is reserved",
+                "breaking: users.user.Role: value ROLE_BOT reuses reserved number 6",
+                "addition: users.user.Role: value ROLE_SERVICE (8) added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_removed_enum() {
+        let previous = enum_set(&[("ROLE_UNSPECIFIED", 0)], &[]);
+
+        pretty_assertions::assert_eq!(
+            descriptions(compare(&previous, &set(vec![]))),
+            vec!["breaking: users.user.Role: enum removed"]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+// Code generated by crunch. DO NOT EDIT.
+
+// Package crunch publishes and subscribes to crunch domain events.
+//
+// Events travel as their protobuf encoding on the subject crunch.{domain}.{entity}.{event},
+// the same as the rust crunch transports, so go and rust services can exchange events.
+package crunch
+
+import (
+	"fmt"
+	"log"
+
+	"google.golang.org/protobuf/proto"
+)
+
+// EventInfo identifies an event, it mirrors crunch::traits::EventInfo.
+type EventInfo struct {
+	Domain     string
+	EntityType string
+	EventName  string
+}
+
+// Subject is the name the event is published under on the transport.
+func (e EventInfo) Subject() string {
+	return fmt.Sprintf("crunch.%s.%s.%s", e.Domain, e.EntityType, e.EventName)
+}
+
+// Event is implemented by every generated event.
+type Event interface {
+	proto.Message
+	EventInfo() EventInfo
+}
+
+// Transport moves raw event payloads between services, i.e. a thin wrapper around a nats connection.
+type Transport interface {
+	Publish(subject string, data []byte) error
+	Subscribe(subject string, handler func(data []byte)) error
+}
+
+type Publisher struct {
+	transport Transport
+}
+
+func NewPublisher(transport Transport) *Publisher {
+	return &Publisher{transport: transport}
+}
+
+func (p *Publisher) Publish(event Event) error {
+	subject := event.EventInfo().Subject()
+
+	data, err := proto.Marshal(event)
+	if err != nil {
+		return fmt.Errorf("failed to serialize %s: %w", subject, err)
+	}
+
+	if err := p.transport.Publish(subject, data); err != nil {
+		return fmt.Errorf("failed to publish %s: %w", subject, err)
+	}
+
+	return nil
+}
+
+type Subscriber struct {
+	transport Transport
+}
+
+func NewSubscriber(transport Transport) *Subscriber {
+	return &Subscriber{transport: transport}
+}
+
+// Subscribe calls handler for every event of type T, events which fail to deserialize are skipped.
+func Subscribe[T any, PT interface {
+	*T
+	Event
+}](s *Subscriber, handler func(PT) error) error {
+	subject := PT(new(T)).EventInfo().Subject()
+
+	return s.transport.Subscribe(subject, func(data []byte) {
+		event := PT(new(T))
+		if err := proto.Unmarshal(data, event); err != nil {
+			log.Printf("crunch: deserialization failed for %s: %v", subject, err)
+			return
+		}
+
+		if err := handler(event); err != nil {
+			log.Printf("crunch: subscription callback failed for %s: %v", subject, err)
+		}
+	})
+}
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, path::Path};
+
+use prost::Message;
+
+use crate::{descriptor, Codegen};
+
+/// The events of a set of schemas along with their descriptors, used to document them
+pub struct SchemaDescription {
+    /// Descriptors of the schemas and everything they import, including comments
+    pub file_descriptor_set: prost_types::FileDescriptorSet,
+    pub events: Vec<EventDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDescription {
+    pub domain: String,
+    pub entity_type: String,
+    pub event_name: String,
+    /// Fully qualified protobuf name of the message, i.e. `users.user.UserCreated`
+    pub message: String,
+    /// Name of the schema file declaring the message, relative to the schema path
+    pub file: String,
+}
+
+impl EventDescription {
+    /// The subject the event is published under by the transports
+    pub fn subject(&self) -> String {
+        format!(
+            "crunch.{}.{}.{}",
+            self.domain, self.entity_type, self.event_name
+        )
+    }
+}
+
+impl Codegen {
+    pub async fn describe(&self, input_path: &Path) -> anyhow::Result<SchemaDescription> {
+        let schemas = self.load_schemas(input_path).await?;
+        let file_descriptor_set =
+            prost_types::FileDescriptorSet::decode(schemas.file_descriptor_set.as_slice())?;
+
+        let mut events = Vec::new();
+        for file in &file_descriptor_set.file {
+            if !schemas.files.contains(file.name()) {
+                continue;
+            }
+
+            let Some(package_events) = schemas.events.get(file.package()) else {
+                continue;
+            };
+            for event in package_events {
+                // Events are grouped by package, only take those declared in this file
+                let top_level = event.message_name.split('.').next().unwrap_or_default();
+                if !file.message_type.iter().any(|m| m.name() == top_level) {
+                    continue;
+                }
+
+                events.push(EventDescription {
+                    domain: self.domain(file.package()),
+                    entity_type: descriptor::package_entity(file.package()).to_string(),
+                    event_name: event.event_name.clone(),
+                    message: qualify(file.package(), &event.message_name),
+                    file: file.name().to_string(),
+                });
+            }
+        }
+
+        Ok(SchemaDescription {
+            file_descriptor_set,
+            events,
+        })
+    }
+}
+
+/// Leading comments of messages, fields and enums, taken from the source info in a descriptor set
+#[derive(Debug, Default)]
+pub struct Comments {
+    messages: HashMap<String, String>,
+    fields: HashMap<(String, String), String>,
+}
+
+impl Comments {
+    pub fn new(file_descriptor_set: &prost_types::FileDescriptorSet) -> Self {
+        let mut comments = Self::default();
+        for file in &file_descriptor_set.file {
+            let Some(source_code_info) = &file.source_code_info else {
+                continue;
+            };
+            let locations = source_code_info
+                .location
+                .iter()
+                .filter_map(|l| {
+                    l.leading_comments
+                        .as_deref()
+                        .map(|c| (l.path.clone(), c.trim().to_string()))
+                })
+                .filter(|(_, c)| !c.is_empty())
+                .collect::<HashMap<_, _>>();
+
+            for (i, message) in file.message_type.iter().enumerate() {
+                comments.collect_message(
+                    file.package(),
+                    message,
+                    vec![MESSAGE_TYPE, i as i32],
+                    &locations,
+                );
+            }
+            for (i, enum_type) in file.enum_type.iter().enumerate() {
+                if let Some(comment) = locations.get(&vec![ENUM_TYPE, i as i32]) {
+                    comments
+                        .messages
+                        .insert(qualify(file.package(), enum_type.name()), comment.clone());
+                }
+            }
+        }
+
+        comments
+    }
+
+    /// Merges in the comments of another descriptor set
+    pub fn extend(&mut self, other: Comments) {
+        self.messages.extend(other.messages);
+        self.fields.extend(other.fields);
+    }
+
+    /// Comment of a message or enum by its fully qualified name
+    pub fn message(&self, message: &str) -> Option<&str> {
+        self.messages.get(message).map(|c| c.as_str())
+    }
+
+    pub fn field(&self, message: &str, field: &str) -> Option<&str> {
+        self.fields
+            .get(&(message.to_string(), field.to_string()))
+            .map(|c| c.as_str())
+    }
+
+    fn collect_message(
+        &mut self,
+        scope: &str,
+        message: &prost_types::DescriptorProto,
+        path: Vec<i32>,
+        locations: &HashMap<Vec<i32>, String>,
+    ) {
+        let name = qualify(scope, message.name());
+        if let Some(comment) = locations.get(&path) {
+            self.messages.insert(name.clone(), comment.clone());
+        }
+
+        for (i, field) in message.field.iter().enumerate() {
+            let mut field_path = path.clone();
+            field_path.extend([FIELD, i as i32]);
+            if let Some(comment) = locations.get(&field_path) {
+                self.fields
+                    .insert((name.clone(), field.name().to_string()), comment.clone());
+            }
+        }
+        for (i, nested) in message.nested_type.iter().enumerate() {
+            let mut nested_path = path.clone();
+            nested_path.extend([NESTED_TYPE, i as i32]);
+            self.collect_message(&name, nested, nested_path, locations);
+        }
+        for (i, enum_type) in message.enum_type.iter().enumerate() {
+            let mut enum_path = path.clone();
+            enum_path.extend([NESTED_ENUM_TYPE, i as i32]);
+            if let Some(comment) = locations.get(&enum_path) {
+                self.messages
+                    .insert(qualify(&name, enum_type.name()), comment.clone());
+            }
+        }
+    }
+}
+
+// Field numbers in descriptor.proto, which make up the paths in its source code info
+const MESSAGE_TYPE: i32 = 4;
+const ENUM_TYPE: i32 = 5;
+const FIELD: i32 = 2;
+const NESTED_TYPE: i32 = 3;
+const NESTED_ENUM_TYPE: i32 = 4;
+
+/// `users.user` and `UserCreated` becomes `users.user.UserCreated`
+pub(crate) fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, HashSet};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use prost::Message;
+use regex::Regex;
+
+/// Path the crunch options proto is made available under, import it with `import "crunch/options.proto";`
+pub const OPTIONS_PROTO_PATH: &str = "crunch/options.proto";
+pub const OPTIONS_PROTO: &str = include_str!("options.proto");
+
+// prost drops unknown fields, so `prost_types::MessageOptions` cannot tell us about custom options.
+// These mirror the subset of descriptor.proto we need, with the crunch extension as a known field.
+#[derive(Clone, PartialEq, Message)]
+struct FileDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<FileDescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct FileDescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    package: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    message_type: Vec<DescriptorProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct DescriptorProto {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(message, repeated, tag = "3")]
+    nested_type: Vec<DescriptorProto>,
+    #[prost(message, optional, tag = "7")]
+    options: Option<MessageOptions>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MessageOptions {
+    // `(crunch.event)`, see options.proto
+    #[prost(bool, optional, tag = "50000")]
+    event: Option<bool>,
+}
+
+/// An event discovered in the descriptor set
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventMessage {
+    /// Path of the generated rust type relative to the package module, i.e. `MyEvent` or `outer::Nested`
+    pub rust_path: String,
+    /// Name of the generated go type, i.e. `MyEvent` or `Outer_Nested`
+    pub go_name: String,
+    /// Name relative to the protobuf package, i.e. `MyEvent` or `Outer.Nested`
+    pub message_name: String,
+    /// Used as the event name, i.e. `MyEvent` or `Outer_Nested`. Nested names are joined with `_` like the go name, as
+    /// the event name is a single token of `crunch.{domain}.{entity}.{event}`
+    pub event_name: String,
+}
+
+/// Finds all messages marked as events, grouped by their protobuf package.
+///
+/// A message is an event if it sets `option (crunch.event) = true;` or its name matches `event_pattern`.
+/// Only files contained in `files` are considered, such that imported dependencies aren't turned into events.
+pub fn discover_events(
+    file_descriptor_set: &[u8],
+    files: &HashSet<String>,
+    event_pattern: Option<&Regex>,
+) -> anyhow::Result<BTreeMap<String, Vec<EventMessage>>> {
+    let set = FileDescriptorSet::decode(file_descriptor_set)?;
+
+    let mut events: BTreeMap<String, Vec<EventMessage>> = BTreeMap::new();
+    for file in set.file {
+        if !files.contains(file.name()) {
+            continue;
+        }
+
+        let file_events = events.entry(file.package().to_string()).or_default();
+        for message in &file.message_type {
+            collect_events(message, "", &[], event_pattern, file_events);
+        }
+    }
+
+    for file_events in events.values_mut() {
+        file_events.sort();
+    }
+
+    Ok(events)
+}
+
+/// Maps every file in the descriptor set, including imports, to its protobuf package
+pub fn file_packages(file_descriptor_set: &[u8]) -> anyhow::Result<BTreeMap<String, String>> {
+    let set = FileDescriptorSet::decode(file_descriptor_set)?;
+
+    Ok(set
+        .file
+        .into_iter()
+        .map(|f| (f.name().to_string(), f.package().to_string()))
+        .collect())
+}
+
+/// The entity of a package is its last segment, i.e. `my_event` for `basic.my_event`
+pub fn package_entity(package: &str) -> &str {
+    package.rsplit('.').next().unwrap_or(package)
+}
+
+/// The domain of a package is everything before the entity, i.e. `basic` for `basic.my_event`
+pub fn package_domain(package: &str) -> &str {
+    package
+        .rsplit_once('.')
+        .map(|(domain, _)| domain)
+        .unwrap_or(package)
+}
+
+fn collect_events(
+    message: &DescriptorProto,
+    parent: &str,
+    rust_modules: &[String],
+    event_pattern: Option<&Regex>,
+    events: &mut Vec<EventMessage>,
+) {
+    let name = message.name();
+    let path = if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent}.{name}")
+    };
+
+    let marked = message
+        .options
+        .as_ref()
+        .and_then(|o| o.event)
+        .unwrap_or(false);
+    let matched = event_pattern.map(|p| p.is_match(name)).unwrap_or(false);
+
+    if marked || matched {
+        let mut rust_path = rust_modules.to_vec();
+        rust_path.push(name.to_upper_camel_case());
+
+        tracing::trace!("discovered event: {}", path);
+        events.push(EventMessage {
+            rust_path: rust_path.join("::"),
+            go_name: path
+                .split('.')
+                .map(|s| s.to_upper_camel_case())
+                .collect::<Vec<_>>()
+                .join("_"),
+            event_name: path.replace('.', "_"),
+            message_name: path.clone(),
+        });
+    }
+
+    // prost-build places nested messages in a module named after their parent
+    let mut nested_modules = rust_modules.to_vec();
+    nested_modules.push(name.to_snake_case());
+    for nested in &message.nested_type {
+        collect_events(nested, &path, &nested_modules, event_pattern, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(name: &str, event: bool, nested: Vec<DescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.into()),
+            nested_type: nested,
+            options: event.then_some(MessageOptions { event: Some(true) }),
+        }
+    }
+
+    fn file_descriptor_set() -> Vec<u8> {
+        FileDescriptorSet {
+            file: vec![
+                FileDescriptorProto {
+                    name: Some("my_event.proto".into()),
+                    package: Some("basic.my_event".into()),
+                    message_type: vec![
+                        message(
+                            "MyEvent",
+                            true,
+                            vec![message(
+                                "NestedEvent",
+                                true,
+                                vec![message("Inner", false, vec![])],
+                            )],
+                        ),
+                        message("Helper", false, vec![]),
+                        message("OtherCreated", false, vec![]),
+                    ],
+                },
+                FileDescriptorProto {
+                    name: Some("google/protobuf/timestamp.proto".into()),
+                    package: Some("google.protobuf".into()),
+                    message_type: vec![message("Timestamp", true, vec![])],
+                },
+            ],
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_discover_events_from_option() -> anyhow::Result<()> {
+        let files = HashSet::from(["my_event.proto".to_string()]);
+
+        let events = discover_events(&file_descriptor_set(), &files, None)?;
+
+        // Nested events stay a single token of the subject
+        assert!(events
+            .values()
+            .flatten()
+            .all(|e| !e.event_name.contains('.')));
+        pretty_assertions::assert_eq!(
+            events,
+            BTreeMap::from([(
+                "basic.my_event".to_string(),
+                vec![
+                    EventMessage {
+                        rust_path: "MyEvent".into(),
+                        go_name: "MyEvent".into(),
+                        message_name: "MyEvent".into(),
+                        event_name: "MyEvent".into(),
+                    },
+                    EventMessage {
+                        rust_path: "my_event::NestedEvent".into(),
+                        go_name: "MyEvent_NestedEvent".into(),
+                        message_name: "MyEvent.NestedEvent".into(),
+                        event_name: "MyEvent_NestedEvent".into(),
+                    },
+                ]
+            )])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_events_from_pattern() -> anyhow::Result<()> {
+        let files = HashSet::from(["my_event.proto".to_string()]);
+        let pattern = Regex::new("Created$")?;
+
+        let events = discover_events(&file_descriptor_set(), &files, Some(&pattern))?;
+
+        pretty_assertions::assert_eq!(
+            events
+                .get("basic.my_event")
+                .unwrap()
+                .iter()
+                .map(|e| e.rust_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["MyEvent", "OtherCreated", "my_event::NestedEvent"]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::descriptor::EventMessage;
+
+/// The go runtime shim, placed in the `crunch` package next to the generated events
+pub const RUNTIME: &str = include_str!("crunch.go");
+
+const HEADER: &str = "// Code generated by crunch. DO NOT EDIT.\n\n";
+
+/// Generates the `EventInfo` implementations for the events of a single go package
+pub fn event_file(
+    go_package: &str,
+    package_name: &str,
+    domain: &str,
+    entity_type: &str,
+    events: &[EventMessage],
+) -> String {
+    let mut output = format!(
+        r#"{HEADER}package {package_name}
+
+import "{go_package}/crunch"
+"#
+    );
+
+    for event in events {
+        output.push_str(&format!(
+            r#"
+func (*{}) EventInfo() crunch.EventInfo {{
+	return crunch.EventInfo{{
+		Domain:     "{}",
+		EntityType: "{}",
+		EventName:  "{}",
+	}}
+}}
+"#,
+            event.go_name, domain, entity_type, event.event_name
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_file() -> anyhow::Result<()> {
+        let output = event_file(
+            "github.com/acme/users/gencrunch",
+            "my_event",
+            "users",
+            "my_event",
+            &[
+                EventMessage {
+                    rust_path: "MyEvent".into(),
+                    go_name: "MyEvent".into(),
+                    message_name: "MyEvent".into(),
+                    event_name: "MyEvent".into(),
+                },
+                EventMessage {
+                    rust_path: "my_event::Nested".into(),
+                    go_name: "MyEvent_Nested".into(),
+                    message_name: "MyEvent.Nested".into(),
+                    event_name: "MyEvent_Nested".into(),
+                },
+            ],
+        );
+
+        pretty_assertions::assert_eq!(
+            output,
+            r#"// Code generated by crunch. DO NOT EDIT.
+
+package my_event
+
+import "github.com/acme/users/gencrunch/crunch"
+
+func (*MyEvent) EventInfo() crunch.EventInfo {
+	return crunch.EventInfo{
+		Domain:     "users",
+		EntityType: "my_event",
+		EventName:  "MyEvent",
+	}
+}
+
+func (*MyEvent_Nested) EventInfo() crunch.EventInfo {
+	return crunch.EventInfo{
+		Domain:     "users",
+		EntityType: "my_event",
+		EventName:  "MyEvent_Nested",
+	}
+}
+"#
+        );
+
+        Ok(())
+    }
+}
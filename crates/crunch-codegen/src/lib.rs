@@ -1,23 +1,51 @@
 use anyhow::anyhow;
 use genco::prelude::*;
+use prost::Message;
 use regex::Regex;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
 };
 use tokio::io::AsyncWriteExt;
 use walkdir::WalkDir;
 
+mod asyncapi;
+mod builder;
+mod catalog;
+mod compat;
+mod describe;
+mod descriptor;
+mod go;
+mod output;
+mod target;
+
+pub use asyncapi::asyncapi;
+pub use builder::Builder;
+pub use catalog::{catalog_events, render_catalog, CatalogEvent, CatalogField, CatalogFormat};
+pub use compat::{compare, ChangeKind, SchemaChange};
+pub use describe::{Comments, EventDescription, SchemaDescription};
+pub use descriptor::{EventMessage, OPTIONS_PROTO, OPTIONS_PROTO_PATH};
+pub use output::StaleFile;
+pub use target::{targets, Target};
+
+#[derive(Debug, Clone)]
+struct RustEvent {
+    path: String,
+    domain: String,
+    entity_type: String,
+    event_name: String,
+}
+
 #[derive(Debug)]
 struct Node {
     file: Option<String>,
-    messages: Option<Vec<String>>,
+    messages: Option<Vec<RustEvent>>,
     segment: String,
     children: HashMap<String, Node>,
 }
 
 impl Node {
-    fn new(segment: String, file: Option<String>, messages: Option<Vec<String>>) -> Self {
+    fn new(segment: String, file: Option<String>, messages: Option<Vec<RustEvent>>) -> Self {
         Node {
             file,
             messages,
@@ -26,7 +54,7 @@ impl Node {
         }
     }
 
-    fn insert(&mut self, file_name: &str, messages: Vec<String>) {
+    fn insert(&mut self, file_name: &str, messages: Vec<RustEvent>) {
         let mut node = self;
         let file_name_content = PathBuf::from(file_name);
         let file_name_content = file_name_content.file_stem().unwrap();
@@ -57,7 +85,7 @@ impl Node {
         let mut nodes = self.children.values().collect::<Vec<_>>();
         nodes.sort_by(|a, b| a.segment.cmp(&b.segment));
         for node in nodes {
-            let tokens = node.traverse_indent(0);
+            let tokens = node.traverse_node();
             child_tokens.push(tokens);
         }
 
@@ -66,36 +94,36 @@ impl Node {
         }
     }
 
-    fn traverse_indent(&self, indent: usize) -> genco::lang::rust::Tokens {
+    fn traverse_node(&self) -> genco::lang::rust::Tokens {
         tracing::trace!("node traverse visited: {}", self.segment);
 
         let mut message_tokens = Vec::new();
         if let Some(file) = &self.file {
             if let Some(messages) = &self.messages {
                 for message in messages.iter() {
-                    tracing::trace!("node traverse visited message: {}", message);
+                    tracing::trace!("node traverse visited message: {}", message.path);
                     let tokens: genco::lang::rust::Tokens = quote! {
-                        impl ::crunch::traits::Serializer for $(message) {
+                        impl ::crunch::traits::Serializer for $(&message.path) {
                             fn serialize(&self) -> Result<Vec<u8>, ::crunch::errors::SerializeError> {
                                 Ok(self.encode_to_vec())
                             }
                         }
-                        impl ::crunch::traits::Deserializer for $(message) {
+                        impl ::crunch::traits::Deserializer for $(&message.path) {
                             fn deserialize(raw: Vec<u8>) -> Result<Self, ::crunch::errors::DeserializeError>
                             where
                                 Self: Sized,
                             {
-                                let output  = Self::decode(raw.as_slice()).map_err(|e| ::crunch::errors::DeserializeError::ProtoErr(e))?;
+                                let output  = Self::decode(raw.as_slice()).map_err(::crunch::errors::DeserializeError::ProtoErr)?;
                                 Ok(output)
                             }
                         }
 
-                        impl crunch::traits::Event for $(message) {
+                        impl crunch::traits::Event for $(&message.path) {
                             fn event_info() -> ::crunch::traits::EventInfo {
                                 ::crunch::traits::EventInfo {
-                                    domain: "my-domain".into(),
-                                    entity_type: "my-entity-type".into(),
-                                    event_name: "my-event-name".into(),
+                                    domain: $(quoted(&message.domain)).into(),
+                                    entity_type: $(quoted(&message.entity_type)).into(),
+                                    event_name: $(quoted(&message.event_name)).into(),
                                 }
                             }
                         }
@@ -105,11 +133,19 @@ impl Node {
                 }
             }
 
-            quote! {
-                pub mod $(&self.segment) {
-                    use prost::Message;
-                    include!($(quoted(file)));
-                    $(for tokens in message_tokens join ($['\r']) => $tokens)
+            if message_tokens.is_empty() {
+                quote! {
+                    pub mod $(&self.segment) {
+                        include!($(quoted(file)));
+                    }
+                }
+            } else {
+                quote! {
+                    pub mod $(&self.segment) {
+                        use prost::Message;
+                        include!($(quoted(file)));
+                        $(for tokens in message_tokens join ($['\r']) => $tokens)
+                    }
                 }
             }
         } else {
@@ -117,7 +153,7 @@ impl Node {
             let mut nodes = self.children.values().collect::<Vec<_>>();
             nodes.sort_by(|a, b| a.segment.cmp(&b.segment));
             for node in nodes {
-                let tokens = node.traverse_indent(indent + 1);
+                let tokens = node.traverse_node();
                 child_tokens.push(tokens);
             }
 
@@ -130,28 +166,165 @@ impl Node {
     }
 }
 
-pub struct Codegen {}
+/// Schemas copied into a scratch directory and compiled into a descriptor set
+struct Schemas {
+    dir: tempfile::TempDir,
+    proto_paths: Vec<PathBuf>,
+    files: HashSet<String>,
+    file_descriptor_set: Vec<u8>,
+    events: BTreeMap<String, Vec<EventMessage>>,
+}
+
+pub struct Codegen {
+    domain: Option<String>,
+    event_pattern: Option<String>,
+    go_package: Option<String>,
+}
 
 impl Codegen {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            domain: None,
+            event_pattern: None,
+            go_package: None,
+        }
+    }
+
+    /// Domain events are published under, defaults to the protobuf package without its last segment
+    pub fn with_domain(&mut self, domain: impl Into<String>) -> &mut Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Messages with a name matching `pattern` are generated as events, in addition to those marked with `option (crunch.event) = true;`
+    pub fn with_event_pattern(&mut self, pattern: impl Into<String>) -> &mut Self {
+        self.event_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Go import path of the output directory, i.e. `github.com/acme/users/gencrunch`, required for `generate_go`
+    pub fn with_go_package(&mut self, go_package: impl Into<String>) -> &mut Self {
+        self.go_package = Some(go_package.into());
+        self
     }
 
+    /// Generates rust code into `output_path`, only files whose content changed are written
     pub async fn generate_rust(&self, input_path: &Path, output_path: &Path) -> anyhow::Result<()> {
-        if output_path.exists() {
-            tokio::fs::remove_dir_all(output_path).await?;
-        }
+        let generated = self.render_rust(input_path).await?;
 
-        let input_protos = self.discover_files(input_path, "proto")?;
-        let (input_proto_paths, input_dir) = self.copy_protos(input_protos, input_path).await?;
-        let (output_proto_paths, temp_output_dir) = self
-            .generate_rust_from_proto(input_proto_paths, input_dir.path())
-            .await?;
+        output::write(&generated, output_path).await
+    }
 
-        self.copy_rs(output_proto_paths, output_path, temp_output_dir.path())
-            .await?;
+    /// Returns the files in `output_path` which differ from what `generate_rust` would write
+    pub async fn check_rust(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+    ) -> anyhow::Result<Vec<StaleFile>> {
+        let generated = self.render_rust(input_path).await?;
 
-        Ok(())
+        output::diff(&generated, output_path).await
+    }
+
+    /// Generates go code into `output_path`, only files whose content changed are written
+    pub async fn generate_go(&self, input_path: &Path, output_path: &Path) -> anyhow::Result<()> {
+        let generated = self.render_go(input_path).await?;
+
+        output::write(&generated, output_path).await
+    }
+
+    /// Returns the files in `output_path` which differ from what `generate_go` would write
+    pub async fn check_go(
+        &self,
+        input_path: &Path,
+        output_path: &Path,
+    ) -> anyhow::Result<Vec<StaleFile>> {
+        let generated = self.render_go(input_path).await?;
+
+        output::diff(&generated, output_path).await
+    }
+
+    /// Compiles the schemas in `input_path` into a descriptor set, imported dependencies are left out
+    pub async fn descriptor_set(
+        &self,
+        input_path: &Path,
+    ) -> anyhow::Result<prost_types::FileDescriptorSet> {
+        let schemas = self.load_schemas(input_path).await?;
+
+        let mut file_descriptor_set =
+            prost_types::FileDescriptorSet::decode(schemas.file_descriptor_set.as_slice())?;
+        file_descriptor_set
+            .file
+            .retain(|f| schemas.files.contains(f.name()));
+
+        Ok(file_descriptor_set)
+    }
+
+    async fn render_rust(&self, input_path: &Path) -> anyhow::Result<output::GeneratedFiles> {
+        let schemas = self.load_schemas(input_path).await?;
+        let (output_proto_paths, temp_output_dir) = self.generate_rust_from_proto(&schemas).await?;
+
+        output::read_generated(&output_proto_paths, temp_output_dir.path()).await
+    }
+
+    async fn render_go(&self, input_path: &Path) -> anyhow::Result<output::GeneratedFiles> {
+        let go_package = self
+            .go_package
+            .as_deref()
+            .ok_or(anyhow!("a go package is required to generate go code"))?
+            .trim_end_matches('/');
+
+        let schemas = self.load_schemas(input_path).await?;
+        let (output_go_paths, temp_output_dir) =
+            self.generate_go_from_proto(&schemas, go_package).await?;
+
+        output::read_generated(&output_go_paths, temp_output_dir.path()).await
+    }
+
+    async fn load_schemas(&self, input_path: &Path) -> anyhow::Result<Schemas> {
+        let event_pattern = self
+            .event_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| anyhow!("invalid event pattern: {}", e))?;
+
+        let input_protos = self.discover_files(input_path, "proto")?;
+        let (proto_paths, dir) = self.copy_protos(input_protos, input_path).await?;
+
+        let files = proto_paths
+            .iter()
+            .map(|p| {
+                p.strip_prefix(dir.path())
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+        let file_descriptor_set = self
+            .compile_descriptor_set(&proto_paths, dir.path())
+            .await
+            .map_err(|e| {
+                // protoc reports the copies in the scratch directory, point at the schemas instead
+                anyhow!(e.to_string().replace(
+                    &dir.path().display().to_string(),
+                    &input_path.display().to_string()
+                ))
+            })?;
+        let events =
+            descriptor::discover_events(&file_descriptor_set, &files, event_pattern.as_ref())?;
+
+        Ok(Schemas {
+            dir,
+            proto_paths,
+            files,
+            file_descriptor_set,
+            events,
+        })
+    }
+
+    fn domain(&self, package: &str) -> String {
+        self.domain
+            .clone()
+            .unwrap_or_else(|| descriptor::package_domain(package).to_string())
     }
 
     fn discover_files(&self, input_path: &Path, extension: &str) -> anyhow::Result<Vec<PathBuf>> {
@@ -198,23 +371,38 @@ impl Codegen {
             input_proto_paths.push(in_proto_path);
         }
 
+        let options_proto_path = in_tempdir_path.join(OPTIONS_PROTO_PATH);
+        if !options_proto_path.exists() {
+            if let Some(dir) = options_proto_path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&options_proto_path, OPTIONS_PROTO).await?;
+        }
+
         Ok((input_proto_paths, in_tempdir))
     }
 
     async fn generate_rust_from_proto(
         &self,
-        input_proto_paths: Vec<PathBuf>,
-        in_root_path: &Path,
+        schemas: &Schemas,
     ) -> anyhow::Result<(Vec<PathBuf>, tempfile::TempDir)> {
         let out_tempdir = tempfile::TempDir::new()?;
         let out_tempdir_path = out_tempdir.path();
         let handle = tokio::task::spawn_blocking({
             let out_tempdir_path = out_tempdir_path.to_path_buf();
-            let in_root_path = in_root_path.to_path_buf();
+            let input_files = schemas.files.clone();
+            let file_descriptor_set = schemas.file_descriptor_set.clone();
             move || {
+                let mut file_descriptor_set =
+                    prost_types::FileDescriptorSet::decode(file_descriptor_set.as_slice())?;
+                // Imports such as google/protobuf and crunch/options.proto are not ours to generate
+                file_descriptor_set
+                    .file
+                    .retain(|f| input_files.contains(f.name()));
+
                 prost_build::Config::new()
                     .out_dir(out_tempdir_path)
-                    .compile_protos(input_proto_paths.as_slice(), &[in_root_path])?;
+                    .compile_fds(file_descriptor_set)?;
 
                 Ok(())
             }
@@ -226,24 +414,179 @@ impl Codegen {
         let mut output_paths = self.discover_files(out_tempdir_path, "rs")?;
 
         let mod_path = self
-            .generate_mod_file(out_tempdir_path, &output_paths)
+            .generate_mod_file(out_tempdir_path, &output_paths, &schemas.events)
             .await?;
         output_paths.push(mod_path);
 
         Ok((output_paths, out_tempdir))
     }
 
+    async fn generate_go_from_proto(
+        &self,
+        schemas: &Schemas,
+        go_package: &str,
+    ) -> anyhow::Result<(Vec<PathBuf>, tempfile::TempDir)> {
+        let out_tempdir = tempfile::TempDir::new()?;
+        let out_tempdir_path = out_tempdir.path();
+
+        // Every protobuf package becomes a go package mirroring the rust module tree, the crunch options live next to the runtime
+        let runtime_package = format!("{go_package}/crunch");
+        let file_packages = descriptor::file_packages(&schemas.file_descriptor_set)?;
+        let mut protoc_args = vec![
+            format!("--go_out={}", out_tempdir_path.display()),
+            format!("--go_opt=module={go_package}"),
+            format!("--go_opt=M{OPTIONS_PROTO_PATH}={runtime_package};crunch"),
+        ];
+        for file in &schemas.files {
+            if file == OPTIONS_PROTO_PATH {
+                continue;
+            }
+
+            let package = file_packages.get(file).map(|p| p.as_str()).unwrap_or("");
+
+            protoc_args.push(format!(
+                "--go_opt=M{}={};{}",
+                file,
+                self.go_import_path(go_package, package),
+                self.go_package_name(go_package, package),
+            ));
+        }
+
+        let mut proto_paths = schemas.proto_paths.clone();
+        let options_proto_path = schemas.dir.path().join(OPTIONS_PROTO_PATH);
+        if !proto_paths.contains(&options_proto_path) {
+            proto_paths.push(options_proto_path);
+        }
+        self.run_protoc(schemas.dir.path(), &protoc_args, &proto_paths)
+            .await?;
+
+        let runtime_path = out_tempdir_path.join("crunch").join("crunch.go");
+        tokio::fs::create_dir_all(out_tempdir_path.join("crunch")).await?;
+        tokio::fs::write(&runtime_path, go::RUNTIME).await?;
+
+        for (package, events) in &schemas.events {
+            if events.is_empty() {
+                continue;
+            }
+
+            let package_name = self.go_package_name(go_package, package);
+            let contents = go::event_file(
+                go_package,
+                &package_name,
+                &self.domain(package),
+                descriptor::package_entity(package),
+                events,
+            );
+
+            let package_dir = out_tempdir_path.join(package.replace('.', "/"));
+            tokio::fs::create_dir_all(&package_dir).await?;
+            tokio::fs::write(
+                package_dir.join(format!("{package_name}.crunch.go")),
+                contents,
+            )
+            .await?;
+        }
+
+        let output_paths = self.discover_files(out_tempdir_path, "go")?;
+
+        Ok((output_paths, out_tempdir))
+    }
+
+    fn go_import_path(&self, go_package: &str, package: &str) -> String {
+        if package.is_empty() {
+            go_package.to_string()
+        } else {
+            format!("{}/{}", go_package, package.replace('.', "/"))
+        }
+    }
+
+    fn go_package_name(&self, go_package: &str, package: &str) -> String {
+        if package.is_empty() {
+            go_package
+                .rsplit('/')
+                .next()
+                .unwrap_or(go_package)
+                .replace('-', "_")
+        } else {
+            descriptor::package_entity(package).to_string()
+        }
+    }
+
+    async fn compile_descriptor_set(
+        &self,
+        input_proto_paths: &[PathBuf],
+        in_root_path: &Path,
+    ) -> anyhow::Result<Vec<u8>> {
+        let descriptor_tempdir = tempfile::TempDir::new()?;
+        let descriptor_path = descriptor_tempdir.path().join("file_descriptor_set.bin");
+
+        self.run_protoc(
+            in_root_path,
+            &[
+                "--include_imports".into(),
+                "--include_source_info".into(),
+                format!("--descriptor_set_out={}", descriptor_path.display()),
+            ],
+            input_proto_paths,
+        )
+        .await?;
+
+        Ok(tokio::fs::read(&descriptor_path).await?)
+    }
+
+    async fn run_protoc(
+        &self,
+        in_root_path: &Path,
+        args: &[String],
+        input_proto_paths: &[PathBuf],
+    ) -> anyhow::Result<()> {
+        let protoc = prost_build::protoc_from_env();
+        let mut cmd = tokio::process::Command::new(&protoc);
+        cmd.args(args).arg("-I").arg(in_root_path);
+        if let Some(protoc_include) = prost_build::protoc_include_from_env() {
+            cmd.arg("-I").arg(protoc_include);
+        }
+        cmd.args(input_proto_paths);
+
+        tracing::debug!("running: {:?}", cmd);
+        let output = cmd.output().await.map_err(|e| {
+            anyhow!(
+                "failed to invoke protoc: {}, error: {}",
+                protoc.display(),
+                e
+            )
+        })?;
+        if !output.status.success() {
+            anyhow::bail!("protoc failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        Ok(())
+    }
+
     async fn generate_mod_file(
         &self,
         output_tempdir_path: &Path,
         output_paths: &[PathBuf],
+        events: &BTreeMap<String, Vec<EventMessage>>,
     ) -> anyhow::Result<PathBuf> {
         let mod_path = output_tempdir_path.join("mod.rs");
         let mut mod_file = tokio::fs::File::create(&mod_path).await?;
         let mut node = Node::new("root".into(), None, None);
 
-        let regex = Regex::new(r"pub struct (?P<eventName>[a-zA-Z0-9-_]+)")
-            .expect("regex to be well formed");
+        let mut rust_events: HashMap<String, Vec<RustEvent>> = HashMap::new();
+        for (package, events) in events {
+            let file_name =
+                prost_build::Module::from_protobuf_package_name(package).to_file_name_or("_");
+            rust_events
+                .entry(file_name)
+                .or_default()
+                .extend(events.iter().map(|e| RustEvent {
+                    path: e.rust_path.clone(),
+                    domain: self.domain(package),
+                    entity_type: descriptor::package_entity(package).to_string(),
+                    event_name: e.event_name.clone(),
+                }));
+        }
 
         let mut output_paths = output_paths.to_vec();
         output_paths.sort();
@@ -251,13 +594,7 @@ impl Codegen {
         for generated_file in output_paths {
             if let Some(name) = generated_file.file_name() {
                 let file_name = name.to_str().unwrap();
-                let file = tokio::fs::read_to_string(&generated_file).await?;
-                let mut messages = regex
-                    .captures_iter(&file)
-                    .map(|m| m.name("eventName").unwrap())
-                    .map(|m| m.as_str().to_string())
-                    .collect::<Vec<_>>();
-                messages.sort();
+                let messages = rust_events.remove(file_name).unwrap_or_default();
 
                 node.insert(file_name, messages);
             }
@@ -265,39 +602,12 @@ impl Codegen {
         let mod_tokens: genco::lang::rust::Tokens = genco::quote! {
             $(node.traverse())
         };
-        let mod_contents = mod_tokens.to_file_string()?;
+        // genco can't preserve line breaks on stable, format it the same way prost-build formats its output
+        let mod_contents = prettyplease::unparse(&syn::parse_file(&mod_tokens.to_file_string()?)?);
         mod_file.write_all(mod_contents.as_bytes()).await?;
 
         Ok(mod_path)
     }
-
-    async fn copy_rs(
-        &self,
-        output_proto_paths: Vec<PathBuf>,
-        output_path: &Path,
-        root_path: &Path,
-    ) -> anyhow::Result<()> {
-        for output_rs in &output_proto_paths {
-            let rel_proto_path = output_rs.strip_prefix(root_path).map_err(|e| {
-                anyhow!(
-                    "output: {} does not match root_path: {}, error: {}",
-                    output_rs.display(),
-                    root_path.display(),
-                    e
-                )
-            })?;
-            let in_proto_path = output_path.join(rel_proto_path);
-            if let Some(dir) = in_proto_path.parent() {
-                if !dir.exists() {
-                    tokio::fs::create_dir_all(dir).await?;
-                }
-            }
-
-            tokio::fs::copy(output_rs, &in_proto_path).await?;
-        }
-
-        Ok(())
-    }
 }
 
 impl Default for Codegen {
@@ -0,0 +1,228 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use walkdir::WalkDir;
+
+/// A file in the output directory which doesn't match what would be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleFile {
+    /// Path of the file in the output directory
+    pub path: PathBuf,
+    /// Unified diff from the existing to the generated content
+    pub diff: String,
+}
+
+/// Generated file contents by their path relative to the output directory
+pub(crate) type GeneratedFiles = BTreeMap<PathBuf, Vec<u8>>;
+
+pub(crate) async fn read_generated(
+    generated_paths: &[PathBuf],
+    root_path: &Path,
+) -> anyhow::Result<GeneratedFiles> {
+    let mut generated = GeneratedFiles::new();
+    for generated_path in generated_paths {
+        let rel_path = generated_path.strip_prefix(root_path).map_err(|e| {
+            anyhow!(
+                "output: {} does not match root_path: {}, error: {}",
+                generated_path.display(),
+                root_path.display(),
+                e
+            )
+        })?;
+
+        generated.insert(
+            rel_path.to_path_buf(),
+            tokio::fs::read(generated_path).await?,
+        );
+    }
+
+    Ok(generated)
+}
+
+/// Compares the generated files against the output directory, files which wouldn't be generated are stale as well
+pub(crate) async fn diff(
+    generated: &GeneratedFiles,
+    output_path: &Path,
+) -> anyhow::Result<Vec<StaleFile>> {
+    let mut stale = Vec::new();
+    for (rel_path, content) in generated {
+        let path = output_path.join(rel_path);
+        let existing = read_existing(&path).await?;
+        if existing.as_deref() == Some(content.as_slice()) {
+            continue;
+        }
+
+        let diff = unified_diff(existing.as_deref(), Some(content), &path);
+        stale.push(StaleFile { path, diff });
+    }
+
+    for rel_path in existing_files(output_path)? {
+        if generated.contains_key(&rel_path) {
+            continue;
+        }
+
+        let path = output_path.join(rel_path);
+        let existing = tokio::fs::read(&path).await?;
+        let diff = unified_diff(Some(&existing), None, &path);
+        stale.push(StaleFile { path, diff });
+    }
+
+    stale.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stale)
+}
+
+/// Writes the generated files, files with unchanged content are left alone so their mtime stays stable
+pub(crate) async fn write(generated: &GeneratedFiles, output_path: &Path) -> anyhow::Result<()> {
+    for (rel_path, content) in generated {
+        let path = output_path.join(rel_path);
+        if read_existing(&path).await?.as_deref() == Some(content.as_slice()) {
+            tracing::trace!("unchanged: {}", path.display());
+            continue;
+        }
+
+        if let Some(dir) = path.parent() {
+            if !dir.exists() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+        }
+        tracing::debug!("writing: {}", path.display());
+        tokio::fs::write(&path, content).await?;
+    }
+
+    // The output directory is owned by codegen, anything else is left over from a previous generation
+    for rel_path in existing_files(output_path)? {
+        if !generated.contains_key(&rel_path) {
+            let path = output_path.join(rel_path);
+            tracing::debug!("removing: {}", path.display());
+            tokio::fs::remove_file(&path).await?;
+        }
+    }
+    remove_empty_dirs(output_path).await?;
+
+    Ok(())
+}
+
+async fn read_existing(path: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    match tokio::fs::read(path).await {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn existing_files(output_path: &Path) -> anyhow::Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    if !output_path.exists() {
+        return Ok(files);
+    }
+
+    for entry in WalkDir::new(output_path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.insert(entry.path().strip_prefix(output_path)?.to_path_buf());
+        }
+    }
+
+    Ok(files)
+}
+
+async fn remove_empty_dirs(output_path: &Path) -> anyhow::Result<()> {
+    if !output_path.exists() {
+        return Ok(());
+    }
+
+    for entry in WalkDir::new(output_path).min_depth(1).contents_first(true) {
+        let entry = entry?;
+        if entry.file_type().is_dir() && std::fs::read_dir(entry.path())?.next().is_none() {
+            tokio::fs::remove_dir(entry.path()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn unified_diff(existing: Option<&[u8]>, generated: Option<&[u8]>, path: &Path) -> String {
+    let existing_content = String::from_utf8_lossy(existing.unwrap_or_default());
+    let generated_content = String::from_utf8_lossy(generated.unwrap_or_default());
+    let display_path = path.display().to_string();
+
+    let old_header = if existing.is_some() {
+        display_path.as_str()
+    } else {
+        "/dev/null"
+    };
+    let new_header = if generated.is_some() {
+        display_path.as_str()
+    } else {
+        "/dev/null"
+    };
+
+    let diff = similar::TextDiff::from_lines(existing_content.as_ref(), generated_content.as_ref())
+        .unified_diff()
+        .header(old_header, new_header)
+        .to_string();
+    if diff.is_empty() {
+        // Empty files being added or removed have no lines to show
+        return format!("--- {old_header}\n+++ {new_header}\n");
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_diff_and_write() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output_path = dir.path().join("gencrunch");
+        tokio::fs::create_dir_all(output_path.join("old")).await?;
+        tokio::fs::write(output_path.join("mod.rs"), "pub mod a;\n").await?;
+        tokio::fs::write(output_path.join("unchanged.rs"), "same\n").await?;
+        tokio::fs::write(output_path.join("old/removed.rs"), "gone\n").await?;
+
+        let generated = GeneratedFiles::from([
+            (PathBuf::from("mod.rs"), b"pub mod b;\n".to_vec()),
+            (PathBuf::from("unchanged.rs"), b"same\n".to_vec()),
+            (PathBuf::from("new.rs"), b"new\n".to_vec()),
+        ]);
+
+        let stale = diff(&generated, &output_path).await?;
+        pretty_assertions::assert_eq!(
+            stale
+                .iter()
+                .map(|s| s.path.strip_prefix(&output_path).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new("mod.rs"),
+                Path::new("new.rs"),
+                Path::new("old/removed.rs")
+            ]
+        );
+        let mod_path = output_path.join("mod.rs").display().to_string();
+        pretty_assertions::assert_eq!(
+            stale[0].diff,
+            format!("--- {mod_path}\n+++ {mod_path}\n@@ -1 +1 @@\n-pub mod a;\n+pub mod b;\n")
+        );
+
+        let unchanged_modified = tokio::fs::metadata(output_path.join("unchanged.rs"))
+            .await?
+            .modified()?;
+        write(&generated, &output_path).await?;
+
+        assert!(diff(&generated, &output_path).await?.is_empty());
+        assert!(!output_path.join("old").exists());
+        pretty_assertions::assert_eq!(
+            tokio::fs::metadata(output_path.join("unchanged.rs"))
+                .await?
+                .modified()?,
+            unchanged_modified
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use crunch_file::{Config, Publish, Registry, Subscription};
+
+use crate::Codegen;
+
+/// A set of schemas to generate code for, either our own published events or those of a subscription
+pub struct Target {
+    pub domain: String,
+    pub schema_path: PathBuf,
+    pub output_path: String,
+    pub go_output_path: Option<String>,
+    pub go_package: Option<String>,
+    pub event_pattern: Option<String>,
+}
+
+impl Target {
+    pub fn from_publish(publish: &Publish, domain: &str) -> Self {
+        Self {
+            domain: domain.to_string(),
+            schema_path: PathBuf::from(&publish.schema_path),
+            output_path: publish.output_path.clone(),
+            go_output_path: publish.go_output_path.clone(),
+            go_package: publish.go_package.clone(),
+            event_pattern: publish.event_pattern.clone(),
+        }
+    }
+
+    /// Subscriptions generate the events of another domain, so the event info has to carry their domain
+    pub fn from_subscription(
+        subscription: &Subscription,
+        registry: Option<&Registry>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            domain: subscription.domain.clone(),
+            schema_path: subscription.resolve_schema_path(registry)?,
+            output_path: subscription.output_path.clone(),
+            go_output_path: subscription.go_output_path.clone(),
+            go_package: subscription.go_package.clone(),
+            event_pattern: subscription.event_pattern.clone(),
+        })
+    }
+
+    /// Output directory for `language`, relative to the crunch file
+    pub fn language_output_path(
+        &self,
+        language: &str,
+        languages: &[String],
+    ) -> anyhow::Result<&str> {
+        match language {
+            "rust" => Ok(&self.output_path),
+            "go" => match &self.go_output_path {
+                Some(go_output_path) => Ok(go_output_path),
+                None if languages.iter().any(|l| l == "rust") => anyhow::bail!(
+                    "go-output-path has to be set for: {}, when generating both rust and go",
+                    self.schema_path.display()
+                ),
+                None => Ok(&self.output_path),
+            },
+            language => anyhow::bail!("codegen not supported for: {}", language),
+        }
+    }
+}
+
+pub fn targets(config: &Config) -> anyhow::Result<Vec<Target>> {
+    let mut targets = Vec::new();
+    for publish in config.publish.iter().flatten() {
+        targets.push(Target::from_publish(publish, &config.service.domain));
+    }
+    for subscription in config.subscription.iter().flatten() {
+        targets.push(Target::from_subscription(
+            subscription,
+            config.registry.as_ref(),
+        )?);
+    }
+
+    // Each generation clears its output directory, sharing one would silently drop code
+    let mut output_paths = HashSet::new();
+    for target in &targets {
+        for language in &config.service.codegen {
+            let output_path = target.language_output_path(language, &config.service.codegen)?;
+            if !output_paths.insert(output_path) {
+                anyhow::bail!("output path: {} is used more than once", output_path);
+            }
+        }
+    }
+
+    Ok(targets)
+}
+
+impl Target {
+    /// A codegen configured for this target
+    pub fn codegen(&self) -> Codegen {
+        let mut codegen = Codegen::new();
+        codegen.with_domain(&self.domain);
+        if let Some(event_pattern) = &self.event_pattern {
+            codegen.with_event_pattern(event_pattern);
+        }
+        if let Some(go_package) = &self.go_package {
+            codegen.with_go_package(go_package);
+        }
+
+        codegen
+    }
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::PersistenceError, EventInfo};
+use wal::{Log, Record};
+
+mod wal;
+
+/// When appends to the log are flushed to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Every append is synced before it returns, nothing is lost on a crash
+    Always,
+    /// Appends are synced once this long has passed since the last sync, and by a background task when no append
+    /// follows. A crash loses at most this window
+    Interval(Duration),
+    /// Leave it to the operating system, a crash of the machine may lose recent events
+    Never,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmbeddedOptions {
+    /// Size in bytes a log segment grows to, before a new one is started. Only whole segments are compacted
+    pub segment_size: u64,
+    pub sync_policy: SyncPolicy,
+}
+
+impl Default for EmbeddedOptions {
+    fn default() -> Self {
+        Self {
+            segment_size: 16 * 1024 * 1024,
+            sync_policy: SyncPolicy::Always,
+        }
+    }
+}
+
+pub struct EmbeddedTx {}
+
+impl crunch_traits::Tx for EmbeddedTx {}
+
+struct State {
+    log: Log,
+    /// Events not yet published, keyed by their offset in the log
+    pending: BTreeMap<u64, (EventInfo, Vec<u8>)>,
+    /// Pending events handed out by `next`, they are handed out again after a restart
+    claimed: BTreeSet<u64>,
+    next_offset: u64,
+}
+
+/// Persistence backed by a write-ahead log in a local directory, for services without a database.
+///
+/// Events are appended to the log on insert, and marked as published by appending a marker. On open the log is
+/// replayed to recover the pending events, and segments holding only published events are deleted as the outbox drains
+#[derive(Clone)]
+pub struct EmbeddedPersistence {
+    state: Arc<Mutex<State>>,
+}
+
+impl EmbeddedPersistence {
+    pub async fn open(dir: impl AsRef<Path>, options: EmbeddedOptions) -> anyhow::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let (log, recovered) = tokio::task::spawn_blocking(move || {
+            Log::open(&dir, options.segment_size, options.sync_policy)
+        })
+        .await??;
+
+        tracing::debug!(
+            pending = recovered.pending.len(),
+            "recovered embedded outbox"
+        );
+
+        let state = Arc::new(Mutex::new(State {
+            log,
+            pending: recovered.pending,
+            claimed: BTreeSet::new(),
+            next_offset: recovered.next_offset,
+        }));
+        if let SyncPolicy::Interval(interval) = options.sync_policy {
+            if !interval.is_zero() {
+                tokio::spawn(sync_interval(Arc::downgrade(&state), interval));
+            }
+        }
+
+        Ok(Self { state })
+    }
+
+    /// Flushes the log to disk, for use on shutdown when not syncing every append
+    pub async fn sync(&self) -> anyhow::Result<()> {
+        self.with_state(|state| state.log.sync()).await
+    }
+
+    // The log is written with blocking io, so it is kept off the async runtime
+    async fn with_state<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut State) -> anyhow::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let state = self.state.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut state = state
+                .lock()
+                .map_err(|_| anyhow::anyhow!("embedded persistence lock is poisoned"))?;
+            f(&mut state)
+        })
+        .await?
+    }
+}
+
+// Without it the last appends before a quiet period would stay unsynced until the next append
+async fn sync_interval(state: Weak<Mutex<State>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        let Some(state) = state.upgrade() else {
+            // The persistence was dropped
+            return;
+        };
+
+        let result = tokio::task::spawn_blocking(move || {
+            let mut state = state
+                .lock()
+                .map_err(|_| anyhow::anyhow!("embedded persistence lock is poisoned"))?;
+            state.log.sync_unsynced()
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        if let Err(e) = result {
+            tracing::warn!("failed to sync embedded outbox: {}", e);
+        }
+    }
+}
+
+fn parse_id(event_id: &str) -> anyhow::Result<u64> {
+    event_id
+        .parse()
+        .map_err(|e| anyhow::anyhow!("invalid event id: {}: {}", event_id, e))
+}
+
+#[async_trait]
+impl crunch_traits::Persistence for EmbeddedPersistence {
+    async fn insert(&self, event_info: &EventInfo, content: Vec<u8>) -> anyhow::Result<()> {
+        let event_info = event_info.to_owned();
+        self.with_state(move |state| {
+            let offset = state.next_offset;
+            state.log.append(&Record::Event {
+                offset,
+                event_info: event_info.clone(),
+                content: content.clone(),
+            })?;
+            state.next_offset += 1;
+            state.pending.insert(offset, (event_info, content));
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn next(&self) -> Result<Option<(String, crunch_traits::DynTx)>, PersistenceError> {
+        let offset = self
+            .with_state(|state| {
+                let offset = state
+                    .pending
+                    .keys()
+                    .find(|o| !state.claimed.contains(*o))
+                    .copied();
+                if let Some(offset) = offset {
+                    state.claimed.insert(offset);
+                }
+
+                Ok(offset)
+            })
+            .await
+            .map_err(PersistenceError::AnyErr)?;
+
+        Ok(offset.map(|o| {
+            (
+                o.to_string(),
+                Box::new(EmbeddedTx {}) as crunch_traits::DynTx,
+            )
+        }))
+    }
+
+    async fn get(&self, event_id: &str) -> Result<Option<(EventInfo, Vec<u8>)>, PersistenceError> {
+        let offset = parse_id(event_id).map_err(PersistenceError::GetErr)?;
+
+        self.with_state(move |state| Ok(state.pending.get(&offset).cloned()))
+            .await
+            .map_err(PersistenceError::GetErr)
+    }
+
+    async fn update_published(&self, event_id: &str) -> Result<(), PersistenceError> {
+        let offset = parse_id(event_id).map_err(PersistenceError::UpdatePublished)?;
+
+        self.with_state(move |state| {
+            if !state.pending.contains_key(&offset) {
+                anyhow::bail!("event was not found on id: {}", offset);
+            }
+            state.log.append(&Record::Published { offset })?;
+            state.pending.remove(&offset);
+            state.claimed.remove(&offset);
+
+            let checkpoint = state
+                .pending
+                .keys()
+                .next()
+                .copied()
+                .unwrap_or(state.next_offset);
+            state.log.compact(checkpoint)
+        })
+        .await
+        .map_err(PersistenceError::UpdatePublished)
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crunch_traits::EventInfo;
+
+use crate::SyncPolicy;
+
+const SEGMENT_EXTENSION: &str = "log";
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+const RECORD_EVENT: u8 = 1;
+const RECORD_PUBLISHED: u8 = 2;
+
+/// A record of the log, each is framed as `len: u32 | crc32: u32 | payload`, all little endian
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Event {
+        offset: u64,
+        event_info: EventInfo,
+        content: Vec<u8>,
+    },
+    Published {
+        offset: u64,
+    },
+}
+
+impl Record {
+    fn offset(&self) -> u64 {
+        match self {
+            Record::Event { offset, .. } | Record::Published { offset } => *offset,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Record::Event {
+                offset,
+                event_info,
+                content,
+            } => {
+                payload.push(RECORD_EVENT);
+                payload.extend_from_slice(&offset.to_le_bytes());
+                for field in [
+                    event_info.domain.as_bytes(),
+                    event_info.entity_type.as_bytes(),
+                    event_info.event_name.as_bytes(),
+                    content,
+                ] {
+                    payload.extend_from_slice(&(field.len() as u32).to_le_bytes());
+                    payload.extend_from_slice(field);
+                }
+            }
+            Record::Published { offset } => {
+                payload.push(RECORD_PUBLISHED);
+                payload.extend_from_slice(&offset.to_le_bytes());
+            }
+        }
+
+        let mut frame = Vec::with_capacity(payload.len() + 8);
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    fn decode(payload: &[u8]) -> Option<Record> {
+        let mut reader = payload;
+        let kind = take(&mut reader, 1)?[0];
+        let offset = u64::from_le_bytes(take(&mut reader, 8)?.try_into().ok()?);
+
+        match kind {
+            RECORD_EVENT => {
+                let mut fields = Vec::with_capacity(4);
+                for _ in 0..4 {
+                    let len = u32::from_le_bytes(take(&mut reader, 4)?.try_into().ok()?);
+                    fields.push(take(&mut reader, len as usize)?.to_vec());
+                }
+                let content = fields.pop()?;
+                let mut strings = fields.into_iter().map(String::from_utf8);
+
+                Some(Record::Event {
+                    offset,
+                    event_info: EventInfo {
+                        domain: strings.next()?.ok()?,
+                        entity_type: strings.next()?.ok()?,
+                        event_name: strings.next()?.ok()?,
+                    },
+                    content,
+                })
+            }
+            RECORD_PUBLISHED => Some(Record::Published { offset }),
+            _ => None,
+        }
+    }
+}
+
+fn take<'a>(reader: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if reader.len() < len {
+        return None;
+    }
+    let (head, tail) = reader.split_at(len);
+    *reader = tail;
+    Some(head)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+struct Segment {
+    path: PathBuf,
+    /// Highest offset an event or published marker of the segment refers to
+    max_offset: Option<u64>,
+}
+
+/// The events which aren't published yet, along with what is needed to continue the log
+pub struct Recovered {
+    pub pending: BTreeMap<u64, (EventInfo, Vec<u8>)>,
+    pub next_offset: u64,
+}
+
+/// An append only log split into numbered segments.
+///
+/// Every offset below the checkpoint is published, segments only referring to those are deleted on compaction
+pub struct Log {
+    dir: PathBuf,
+    segments: Vec<Segment>,
+    next_segment: u64,
+    active: File,
+    active_len: u64,
+    segment_size: u64,
+    sync_policy: SyncPolicy,
+    last_sync: Instant,
+    /// Whether appends were written since the last sync
+    unsynced: bool,
+    checkpoint: u64,
+}
+
+impl Log {
+    /// Opens the log in `dir`, replaying it to find the pending events. A record torn by a crash at the end of the
+    /// last segment is truncated away
+    pub fn open(
+        dir: &Path,
+        segment_size: u64,
+        sync_policy: SyncPolicy,
+    ) -> anyhow::Result<(Self, Recovered)> {
+        std::fs::create_dir_all(dir)?;
+
+        let checkpoint = match std::fs::read_to_string(dir.join(CHECKPOINT_FILE)) {
+            Ok(checkpoint) => checkpoint.trim().parse()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut segment_paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(SEGMENT_EXTENSION))
+            .collect::<Vec<_>>();
+        // Segment names are zero padded, so they sort in the order they were written
+        segment_paths.sort();
+        let mut next_segment = match segment_paths.last() {
+            Some(path) => {
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .ok_or(anyhow::anyhow!(
+                        "invalid log segment name: {}",
+                        path.display()
+                    ))?
+                    + 1
+            }
+            None => 0,
+        };
+
+        let mut pending = BTreeMap::new();
+        let mut next_offset = checkpoint;
+        let mut segments = Vec::new();
+        let segment_count = segment_paths.len();
+        for (i, path) in segment_paths.into_iter().enumerate() {
+            let (records, valid_len) = read_segment(&path)?;
+            let file_len = std::fs::metadata(&path)?.len();
+            if valid_len < file_len {
+                if i + 1 < segment_count {
+                    anyhow::bail!("log segment: {} is corrupt", path.display());
+                }
+                tracing::warn!(
+                    "truncating torn record at the end of log segment: {}",
+                    path.display()
+                );
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)?
+                    .set_len(valid_len)?;
+            }
+
+            let mut max_offset = None;
+            for record in records {
+                let offset = record.offset();
+                max_offset = max_offset.max(Some(offset));
+                match record {
+                    Record::Event {
+                        offset,
+                        event_info,
+                        content,
+                    } => {
+                        next_offset = next_offset.max(offset + 1);
+                        if offset >= checkpoint {
+                            pending.insert(offset, (event_info, content));
+                        }
+                    }
+                    Record::Published { offset } => {
+                        pending.remove(&offset);
+                    }
+                }
+            }
+
+            segments.push(Segment { path, max_offset });
+        }
+
+        if segments.is_empty() {
+            segments.push(Segment {
+                path: segment_path(dir, next_segment),
+                max_offset: None,
+            });
+            next_segment += 1;
+        }
+        let active_path = &segments.last().expect("a segment to exist").path;
+        let active = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(active_path)?;
+        let active_len = active.metadata()?.len();
+
+        let log = Self {
+            dir: dir.to_path_buf(),
+            segments,
+            next_segment,
+            active,
+            active_len,
+            segment_size,
+            sync_policy,
+            last_sync: Instant::now(),
+            unsynced: false,
+            checkpoint,
+        };
+
+        Ok((
+            log,
+            Recovered {
+                pending,
+                next_offset,
+            },
+        ))
+    }
+
+    pub fn append(&mut self, record: &Record) -> anyhow::Result<()> {
+        if self.active_len >= self.segment_size {
+            self.roll()?;
+        }
+
+        let frame = record.encode();
+        self.active.write_all(&frame)?;
+        self.active_len += frame.len() as u64;
+
+        let segment = self.segments.last_mut().expect("a segment to exist");
+        segment.max_offset = segment.max_offset.max(Some(record.offset()));
+
+        let sync = match self.sync_policy {
+            SyncPolicy::Always => true,
+            SyncPolicy::Interval(interval) => self.last_sync.elapsed() >= interval,
+            SyncPolicy::Never => false,
+        };
+        if sync {
+            self.active.sync_data()?;
+            self.last_sync = Instant::now();
+        }
+        self.unsynced = !sync;
+
+        Ok(())
+    }
+
+    fn roll(&mut self) -> anyhow::Result<()> {
+        self.active.sync_all()?;
+
+        let path = segment_path(&self.dir, self.next_segment);
+        self.next_segment += 1;
+        self.active = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.active_len = 0;
+        self.segments.push(Segment {
+            path,
+            max_offset: None,
+        });
+
+        Ok(())
+    }
+
+    /// Records that every offset below `checkpoint` is published, and deletes the segments no longer needed
+    pub fn compact(&mut self, checkpoint: u64) -> anyhow::Result<()> {
+        // Until a segment can go, its published markers are as good as a checkpoint
+        let active = self.segments.len() - 1;
+        let removable = self.segments[..active]
+            .iter()
+            .take_while(|s| s.max_offset.is_none_or(|o| o < checkpoint))
+            .count();
+        if removable == 0 || checkpoint <= self.checkpoint {
+            return Ok(());
+        }
+
+        // The checkpoint has to be durable before the segments it replaces are gone
+        self.active.sync_all()?;
+        let tmp = self.dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        let mut file = File::create(&tmp)?;
+        file.write_all(checkpoint.to_string().as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, self.dir.join(CHECKPOINT_FILE))?;
+        self.checkpoint = checkpoint;
+
+        for segment in self.segments.drain(..removable) {
+            tracing::debug!("removing published log segment: {}", segment.path.display());
+            std::fs::remove_file(&segment.path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn sync(&mut self) -> anyhow::Result<()> {
+        self.active.sync_all()?;
+        self.last_sync = Instant::now();
+        self.unsynced = false;
+
+        Ok(())
+    }
+
+    /// Syncs if anything was appended since the last sync
+    pub fn sync_unsynced(&mut self) -> anyhow::Result<()> {
+        if self.unsynced {
+            self.sync()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", segment, SEGMENT_EXTENSION))
+}
+
+/// The records of the segment, and the length of it which holds complete records
+fn read_segment(path: &Path) -> anyhow::Result<(Vec<Record>, u64)> {
+    let content = std::fs::read(path)?;
+
+    let mut records = Vec::new();
+    let mut reader = content.as_slice();
+    let mut valid_len = 0;
+    while let Some(record) = read_record(&mut reader) {
+        records.push(record);
+        valid_len = (content.len() - reader.len()) as u64;
+    }
+
+    Ok((records, valid_len))
+}
+
+fn read_record(reader: &mut &[u8]) -> Option<Record> {
+    let len = u32::from_le_bytes(take(reader, 4)?.try_into().ok()?);
+    let crc = u32::from_le_bytes(take(reader, 4)?.try_into().ok()?);
+    let payload = take(reader, len as usize)?;
+    if crc32(payload) != crc {
+        return None;
+    }
+
+    Record::decode(payload)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = Record::Event {
+            offset: 42,
+            event_info: EventInfo {
+                domain: "some-domain".into(),
+                entity_type: "some-entity-type".into(),
+                event_name: "some-event-name".into(),
+            },
+            content: b"some-content".to_vec(),
+        };
+
+        let frame = record.encode();
+        let mut reader = frame.as_slice();
+        assert_eq!(Some(record), read_record(&mut reader));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_torn_record_is_rejected() {
+        let frame = Record::Published { offset: 1 }.encode();
+
+        assert_eq!(None, read_record(&mut &frame[..frame.len() - 1]));
+
+        let mut corrupt = frame.clone();
+        *corrupt.last_mut().unwrap() ^= 0xff;
+        assert_eq!(None, read_record(&mut corrupt.as_slice()));
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+    }
+}
@@ -0,0 +1,159 @@
+use crunch_embedded::{EmbeddedOptions, EmbeddedPersistence};
+use crunch_traits::{EventInfo, Persistence};
+
+fn event_info() -> EventInfo {
+    EventInfo {
+        domain: "some-domain".into(),
+        entity_type: "some-entity-type".into(),
+        event_name: "some-event-name".into(),
+    }
+}
+
+fn segments(dir: &std::path::Path) -> anyhow::Result<usize> {
+    Ok(std::fs::read_dir(dir)?
+        .filter(|e| {
+            e.as_ref()
+                .is_ok_and(|e| e.path().extension().is_some_and(|e| e == "log"))
+        })
+        .count())
+}
+
+#[tokio::test]
+async fn test_persistence_publish_flow() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+
+    persistence
+        .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+        .await?;
+
+    let (event_id, _) = persistence.next().await?.unwrap();
+    assert!(persistence.next().await?.is_none());
+
+    let (info, content) = persistence.get(&event_id).await?.unwrap();
+    assert_eq!(event_info(), info);
+    assert_eq!(b"some-strange-and-cruncy-content".to_vec(), content);
+
+    persistence.update_published(&event_id).await?;
+    assert!(persistence.get(&event_id).await?.is_none());
+    assert!(persistence.update_published(&event_id).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_recovers_pending_events() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    {
+        let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+        for content in ["first", "second", "third"] {
+            persistence
+                .insert(&event_info(), content.as_bytes().to_vec())
+                .await?;
+        }
+
+        let (event_id, _) = persistence.next().await?.unwrap();
+        persistence.update_published(&event_id).await?;
+        // Claimed but not published before the "crash"
+        persistence.next().await?.unwrap();
+    }
+
+    let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+    let (event_id, _) = persistence.next().await?.unwrap();
+    assert_eq!(
+        b"second".to_vec(),
+        persistence.get(&event_id).await?.unwrap().1
+    );
+    let (event_id, _) = persistence.next().await?.unwrap();
+    assert_eq!(
+        b"third".to_vec(),
+        persistence.get(&event_id).await?.unwrap().1
+    );
+    assert!(persistence.next().await?.is_none());
+
+    // New events continue after the recovered ones
+    persistence
+        .insert(&event_info(), b"fourth".to_vec())
+        .await?;
+    let (fourth_id, _) = persistence.next().await?.unwrap();
+    assert_ne!(event_id, fourth_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_truncates_torn_record() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+
+    {
+        let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+        persistence.insert(&event_info(), b"first".to_vec()).await?;
+        persistence
+            .insert(&event_info(), b"second".to_vec())
+            .await?;
+    }
+
+    // Simulate a crash halfway through the last append
+    let segment = std::fs::read_dir(dir.path())?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .find(|p| p.extension().is_some_and(|e| e == "log"))
+        .unwrap();
+    let len = std::fs::metadata(&segment)?.len();
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&segment)?
+        .set_len(len - 3)?;
+
+    let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+    let (event_id, _) = persistence.next().await?.unwrap();
+    assert_eq!(
+        b"first".to_vec(),
+        persistence.get(&event_id).await?.unwrap().1
+    );
+    assert!(persistence.next().await?.is_none());
+
+    persistence.insert(&event_info(), b"third".to_vec()).await?;
+    drop(persistence);
+
+    let persistence = EmbeddedPersistence::open(dir.path(), EmbeddedOptions::default()).await?;
+    persistence.next().await?.unwrap();
+    let (event_id, _) = persistence.next().await?.unwrap();
+    assert_eq!(
+        b"third".to_vec(),
+        persistence.get(&event_id).await?.unwrap().1
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_compacts_published_segments() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let options = EmbeddedOptions {
+        // Every record gets its own segment
+        segment_size: 1,
+        ..Default::default()
+    };
+    let persistence = EmbeddedPersistence::open(dir.path(), options.clone()).await?;
+
+    for _ in 0..5 {
+        persistence
+            .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+            .await?;
+    }
+    assert_eq!(5, segments(dir.path())?);
+
+    while let Some((event_id, _)) = persistence.next().await? {
+        persistence.update_published(&event_id).await?;
+    }
+    assert!(segments(dir.path())? < 5);
+    drop(persistence);
+
+    let persistence = EmbeddedPersistence::open(dir.path(), options).await?;
+    assert!(persistence.next().await?.is_none());
+
+    Ok(())
+}
@@ -1,7 +1,19 @@
+mod validate;
+
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
 use toml_edit::{value, Document};
 
+pub use validate::{is_valid_name, Diagnostic, Label};
+
+/// Directory, relative to the crunch file, that schemas fetched from a remote registry are kept in
+pub const SCHEMA_CACHE_DIR: &str = ".crunch/schemas";
+
+/// File at the root of a repository listing the crunch files it contains
+pub const WORKSPACE_FILE: &str = "crunch-workspace.toml";
+
 #[derive(Debug)]
 pub struct File {
     doc: Document,
@@ -11,6 +23,9 @@ pub struct File {
 pub struct Config {
     pub service: Service,
     pub publish: Option<Vec<Publish>>,
+    pub subscription: Option<Vec<Subscription>>,
+    pub registry: Option<Registry>,
+    pub compatibility: Option<Compatibility>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,6 +33,8 @@ pub struct Service {
     pub service: String,
     pub domain: String,
     pub codegen: Vec<String>,
+    /// Version the `publish` schemas are published to the registry under
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -27,6 +44,147 @@ pub struct Publish {
     #[serde(alias = "output-path")]
     pub output_path: String,
     pub entities: Vec<String>,
+    #[serde(alias = "event-pattern")]
+    pub event_pattern: Option<String>,
+    #[serde(alias = "go-output-path")]
+    pub go_output_path: Option<String>,
+    #[serde(alias = "go-package")]
+    pub go_package: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Subscription {
+    pub service: String,
+    pub domain: String,
+    pub version: String,
+    #[serde(alias = "output-path")]
+    pub output_path: String,
+    #[serde(alias = "schema-path")]
+    pub schema_path: Option<String>,
+    #[serde(alias = "event-pattern")]
+    pub event_pattern: Option<String>,
+    #[serde(alias = "go-output-path")]
+    pub go_output_path: Option<String>,
+    #[serde(alias = "go-package")]
+    pub go_package: Option<String>,
+}
+
+impl Subscription {
+    /// The directory holding the subscribed schemas, relative to the crunch file.
+    ///
+    /// An explicit `schema-path` wins, otherwise the schemas are at `domain/service/version`, either directly in a
+    /// directory registry, or in [`SCHEMA_CACHE_DIR`] once fetched from a git registry
+    pub fn resolve_schema_path(&self, registry: Option<&Registry>) -> anyhow::Result<PathBuf> {
+        if let Some(schema_path) = &self.schema_path {
+            return Ok(PathBuf::from(schema_path));
+        }
+
+        let root = match registry {
+            Some(Registry {
+                path: Some(path), ..
+            }) => PathBuf::from(path),
+            Some(Registry { git: Some(_), .. }) => PathBuf::from(SCHEMA_CACHE_DIR),
+            Some(_) => anyhow::bail!("[registry] requires either path or git"),
+            None => anyhow::bail!(
+                "no schema source for subscription: {}/{}, set either schema-path or [registry]",
+                self.domain,
+                self.service
+            ),
+        };
+
+        Ok(root
+            .join(&self.domain)
+            .join(&self.service)
+            .join(&self.version))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Registry {
+    pub path: Option<String>,
+    pub git: Option<String>,
+    pub branch: Option<String>,
+}
+
+/// The version bump `crunch schema check` requires for each kind of schema change
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Compatibility {
+    pub breaking: Bump,
+    pub additions: Bump,
+    pub compatible: Bump,
+}
+
+impl Default for Compatibility {
+    fn default() -> Self {
+        Self {
+            breaking: Bump::Major,
+            additions: Bump::Minor,
+            compatible: Bump::Patch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl std::fmt::Display for Bump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Bump::None => write!(f, "none"),
+            Bump::Patch => write!(f, "patch"),
+            Bump::Minor => write!(f, "minor"),
+            Bump::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// A repository of several services, each with its own crunch file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Workspace {
+    pub workspace: WorkspaceMembers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceMembers {
+    /// Directories holding a `.crunch.toml`, relative to the workspace file
+    pub members: Vec<String>,
+}
+
+impl Workspace {
+    pub async fn parse_file(path: &std::path::Path) -> anyhow::Result<Workspace> {
+        tracing::debug!("loading workspace file at: {}", path.display());
+
+        let file = tokio::fs::read_to_string(path).await?;
+
+        Self::parse(&file)
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Workspace> {
+        Ok(toml_edit::de::from_str(content)?)
+    }
+
+    /// Paths of the crunch files of every member, relative to the workspace file
+    pub fn crunch_files(&self) -> Vec<PathBuf> {
+        self.workspace
+            .members
+            .iter()
+            .map(|member| {
+                let member = PathBuf::from(member);
+                if member.extension().and_then(|e| e.to_str()) == Some("toml") {
+                    member
+                } else {
+                    member.join(".crunch.toml")
+                }
+            })
+            .collect()
+    }
 }
 
 #[allow(dead_code)]
@@ -96,6 +254,33 @@ impl File {
         self
     }
 
+    pub fn add_subscription(
+        &mut self,
+        service: &str,
+        domain: &str,
+        version: &str,
+        output_path: &str,
+    ) -> &mut Self {
+        let mut subscription = toml_edit::Table::new();
+        subscription["service"] = value(service);
+        subscription["domain"] = value(domain);
+        subscription["version"] = value(version);
+        subscription["output-path"] = value(output_path);
+
+        if !self.doc.contains_key("subscription") {
+            tracing::debug!("subscription key not existing, adding new");
+            self.doc["subscription"] = toml_edit::array()
+        }
+
+        tracing::debug!("adding new subscription item");
+        self.doc["subscription"]
+            .as_array_of_tables_mut()
+            .expect("subscription to be present and be array of tables [[subscription]]")
+            .push(subscription);
+
+        self
+    }
+
     pub fn get_config(&self) -> anyhow::Result<Config> {
         let content = self.doc.to_string();
 
@@ -204,9 +389,13 @@ codegen = ["rust"]
                 service: Service {
                     service: "my-service".into(),
                     domain: "my-domain".into(),
-                    codegen: vec!["rust".into()]
+                    codegen: vec!["rust".into()],
+                    version: None,
                 },
-                publish: None
+                publish: None,
+                subscription: None,
+                registry: None,
+                compatibility: None,
             }
         );
 
@@ -234,16 +423,152 @@ entities = []
                 service: Service {
                     service: "my-service".into(),
                     domain: "my-domain".into(),
-                    codegen: vec!["rust".into()]
+                    codegen: vec!["rust".into()],
+                    version: None,
                 },
                 publish: Some(vec![Publish {
                     schema_path: "some-schema".into(),
                     output_path: "some-output".into(),
-                    entities: vec![]
-                }])
+                    entities: vec![],
+                    event_pattern: None,
+                    go_output_path: None,
+                    go_package: None,
+                }]),
+                subscription: None,
+                registry: None,
+                compatibility: None,
             }
         );
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_can_get_config_compatibility() -> anyhow::Result<()> {
+        let raw = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust"]
+
+[compatibility]
+breaking = "minor"
+"#;
+
+        let config = File::parse(raw).await?.get_config()?;
+
+        pretty_assertions::assert_eq!(
+            config.compatibility,
+            Some(Compatibility {
+                breaking: Bump::Minor,
+                additions: Bump::Minor,
+                compatible: Bump::Patch,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_can_parse_workspace() -> anyhow::Result<()> {
+        let raw = r#"[workspace]
+members = ["services/users", "services/orders/.crunch.toml"]
+"#;
+
+        let workspace = Workspace::parse(raw)?;
+
+        pretty_assertions::assert_eq!(
+            workspace.crunch_files(),
+            vec![
+                PathBuf::from("services/users/.crunch.toml"),
+                PathBuf::from("services/orders/.crunch.toml")
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_can_add_subscription() -> anyhow::Result<()> {
+        let raw = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust"]
+"#;
+        let expected = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust"]
+
+[[subscription]]
+service = "other-service"
+domain = "other-domain"
+version = "1.0.1"
+output-path = "some-output"
+"#;
+        let mut config = File::parse(raw).await?;
+        let config =
+            config.add_subscription("other-service", "other-domain", "1.0.1", "some-output");
+        let output = config.write().await?;
+
+        pretty_assertions::assert_eq!(output, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_can_get_config_subscription() -> anyhow::Result<()> {
+        let raw = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust"]
+
+[registry]
+path = "../contracts"
+
+[[subscription]]
+service = "other-service"
+domain = "other-domain"
+version = "1.0.1"
+output-path = "some-output"
+"#;
+
+        let config = File::parse(raw).await?.get_config()?;
+        let subscription = config.subscription.as_ref().unwrap().first().unwrap();
+
+        pretty_assertions::assert_eq!(
+            subscription.resolve_schema_path(config.registry.as_ref())?,
+            PathBuf::from("../contracts/other-domain/other-service/1.0.1")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_can_get_config_subscription_git_registry() -> anyhow::Result<()> {
+        let raw = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = ["rust"]
+
+[registry]
+git = "git@github.com:acme/contracts.git"
+branch = "main"
+
+[[subscription]]
+service = "other-service"
+domain = "other-domain"
+version = "1.0.1"
+output-path = "some-output"
+"#;
+
+        let config = File::parse(raw).await?.get_config()?;
+        let subscription = config.subscription.as_ref().unwrap().first().unwrap();
+
+        pretty_assertions::assert_eq!(
+            subscription.resolve_schema_path(config.registry.as_ref())?,
+            PathBuf::from(".crunch/schemas/other-domain/other-service/1.0.1")
+        );
+
+        Ok(())
+    }
 }
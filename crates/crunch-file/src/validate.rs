@@ -0,0 +1,473 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_spanned::Spanned;
+
+use crate::{Config, File};
+
+const CODEGEN_TARGETS: &[&str] = &["rust", "go"];
+
+/// A problem in a crunch file, spans are byte offsets into the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            labels: Vec::new(),
+            help: None,
+        }
+    }
+
+    fn with_label(mut self, span: Range<usize>, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Locates the error of [`File::parse`] when the document isn't valid toml
+    pub fn from_parse_error(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<toml_edit::TomlError>() {
+            Some(e) => toml_diagnostic(e.message(), e.span()),
+            None => Diagnostic::new(error.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Names of services, domains and entities end up in subjects and generated modules
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+// Mirrors the parts of `Config` which are validated, with their location in the document
+#[derive(Deserialize)]
+struct SpannedConfig {
+    service: SpannedService,
+    publish: Option<Vec<SpannedPublish>>,
+    subscription: Option<Vec<SpannedSubscription>>,
+    registry: Option<Spanned<SpannedRegistry>>,
+}
+
+#[derive(Deserialize)]
+struct SpannedService {
+    service: Spanned<String>,
+    domain: Spanned<String>,
+    codegen: Vec<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+struct SpannedPublish {
+    #[serde(alias = "schema-path")]
+    schema_path: Spanned<String>,
+    #[serde(alias = "output-path")]
+    output_path: Spanned<String>,
+    entities: Vec<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+struct SpannedSubscription {
+    service: Spanned<String>,
+    domain: Spanned<String>,
+    #[serde(alias = "output-path")]
+    output_path: Spanned<String>,
+    #[serde(alias = "schema-path")]
+    schema_path: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+struct SpannedRegistry {
+    path: Option<Spanned<String>>,
+    git: Option<Spanned<String>>,
+}
+
+impl File {
+    /// Checks the crunch file beyond its structure, paths are resolved relative to `crunch_dir`.
+    ///
+    /// Spans refer to the document as returned by [`File::write`]
+    pub fn validate(&self, crunch_dir: &Path) -> Vec<Diagnostic> {
+        let content = self.doc.to_string();
+        if let Err(e) = toml_edit::de::from_str::<Config>(&content) {
+            return vec![toml_diagnostic(e.message(), e.span())];
+        }
+        let config = match toml_edit::de::from_str::<SpannedConfig>(&content) {
+            Ok(config) => config,
+            Err(e) => return vec![toml_diagnostic(e.message(), e.span())],
+        };
+
+        let mut diagnostics = Vec::new();
+        validate_service(&config.service, &mut diagnostics);
+
+        let mut output_paths = HashMap::new();
+        let mut schema_paths: HashMap<&str, Range<usize>> = HashMap::new();
+        for publish in config.publish.iter().flatten() {
+            unique_output_path(&publish.output_path, &mut output_paths, &mut diagnostics);
+
+            if let Some(first) = schema_paths.get(publish.schema_path.get_ref().as_str()) {
+                diagnostics.push(
+                    Diagnostic::new(format!(
+                        "schema path: {} is published more than once",
+                        publish.schema_path.get_ref()
+                    ))
+                    .with_label(first.clone(), "first published here")
+                    .with_label(publish.schema_path.span(), "published again here")
+                    .with_help("merge the entities into a single [[publish]] entry"),
+                );
+                continue;
+            }
+            schema_paths.insert(publish.schema_path.get_ref(), publish.schema_path.span());
+
+            let schema_dir = crunch_dir.join(publish.schema_path.get_ref());
+            if !schema_dir.is_dir() {
+                diagnostics.push(missing_schema_path(&publish.schema_path, &schema_dir));
+                continue;
+            }
+
+            let packages = proto_packages(&schema_dir);
+            for entity in &publish.entities {
+                if !is_valid_name(entity.get_ref()) {
+                    diagnostics.push(invalid_name("entity", entity));
+                    continue;
+                }
+
+                // The entity of an event is the last segment of its package
+                let package_entity = entity.get_ref().replace('-', "_");
+                if !packages
+                    .iter()
+                    .any(|p| p.rsplit('.').next() == Some(package_entity.as_str()))
+                {
+                    diagnostics.push(
+                        Diagnostic::new(format!(
+                            "entity: {} has no schema in: {}",
+                            entity.get_ref(),
+                            publish.schema_path.get_ref()
+                        ))
+                        .with_label(entity.span(), "no proto package ends in this entity")
+                        .with_help(format!(
+                            "add a schema with: package {}.{};",
+                            config.service.domain.get_ref().replace('-', "_"),
+                            package_entity
+                        )),
+                    );
+                }
+            }
+        }
+
+        let mut subscriptions: HashMap<(&str, &str), Range<usize>> = HashMap::new();
+        for subscription in config.subscription.iter().flatten() {
+            unique_output_path(
+                &subscription.output_path,
+                &mut output_paths,
+                &mut diagnostics,
+            );
+
+            for (kind, name) in [
+                ("service", &subscription.service),
+                ("domain", &subscription.domain),
+            ] {
+                if !is_valid_name(name.get_ref()) {
+                    diagnostics.push(invalid_name(kind, name));
+                }
+            }
+
+            let key = (
+                subscription.domain.get_ref().as_str(),
+                subscription.service.get_ref().as_str(),
+            );
+            if let Some(first) = subscriptions.get(&key) {
+                diagnostics.push(
+                    Diagnostic::new(format!(
+                        "subscription to: {}/{} is defined more than once",
+                        key.0, key.1
+                    ))
+                    .with_label(first.clone(), "first subscribed here")
+                    .with_label(subscription.service.span(), "subscribed again here"),
+                );
+            } else {
+                subscriptions.insert(key, subscription.service.span());
+            }
+
+            if let Some(schema_path) = &subscription.schema_path {
+                let schema_dir = crunch_dir.join(schema_path.get_ref());
+                if !schema_dir.is_dir() {
+                    diagnostics.push(missing_schema_path(schema_path, &schema_dir));
+                }
+            } else if config.registry.is_none() {
+                diagnostics.push(
+                    Diagnostic::new(format!(
+                        "no schema source for subscription: {}/{}",
+                        key.0, key.1
+                    ))
+                    .with_label(subscription.service.span(), "subscribed here")
+                    .with_help("set schema-path on the subscription, or configure a [registry]"),
+                );
+            }
+        }
+
+        if let Some(registry) = &config.registry {
+            match registry.get_ref() {
+                SpannedRegistry {
+                    path: Some(path), ..
+                } => {
+                    let registry_dir = crunch_dir.join(path.get_ref());
+                    if !registry_dir.is_dir() {
+                        diagnostics.push(
+                            Diagnostic::new(format!(
+                                "registry not found at: {}",
+                                registry_dir.display()
+                            ))
+                            .with_label(path.span(), "no such directory"),
+                        );
+                    }
+                }
+                SpannedRegistry { git: Some(_), .. } => {}
+                _ => diagnostics.push(
+                    Diagnostic::new("[registry] requires either path or git")
+                        .with_label(registry.span(), "registry without a source"),
+                ),
+            }
+        }
+
+        diagnostics
+    }
+}
+
+fn validate_service(service: &SpannedService, diagnostics: &mut Vec<Diagnostic>) {
+    for (kind, name) in [("service", &service.service), ("domain", &service.domain)] {
+        if !is_valid_name(name.get_ref()) {
+            diagnostics.push(invalid_name(kind, name));
+        }
+    }
+
+    for codegen in &service.codegen {
+        if !CODEGEN_TARGETS.contains(&codegen.get_ref().as_str()) {
+            diagnostics.push(
+                Diagnostic::new(format!("unknown codegen target: {}", codegen.get_ref()))
+                    .with_label(codegen.span(), "not a supported language")
+                    .with_help(format!(
+                        "supported targets are: {}",
+                        CODEGEN_TARGETS.join(", ")
+                    )),
+            );
+        }
+    }
+}
+
+fn unique_output_path<'a>(
+    path: &'a Spanned<String>,
+    output_paths: &mut HashMap<&'a str, Range<usize>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    match output_paths.get(path.get_ref().as_str()) {
+        Some(first) => diagnostics.push(
+            Diagnostic::new(format!(
+                "output path: {} is used more than once",
+                path.get_ref()
+            ))
+            .with_label(first.clone(), "first used here")
+            .with_label(path.span(), "used again here")
+            .with_help("each generation clears its output path, give every entry its own"),
+        ),
+        None => {
+            output_paths.insert(path.get_ref(), path.span());
+        }
+    }
+}
+
+fn invalid_name(kind: &str, name: &Spanned<String>) -> Diagnostic {
+    Diagnostic::new(format!("invalid {} name: {:?}", kind, name.get_ref()))
+        .with_label(name.span(), "invalid name")
+        .with_help("names can only contain lowercase letters, numbers, - and _")
+}
+
+fn missing_schema_path(schema_path: &Spanned<String>, schema_dir: &Path) -> Diagnostic {
+    Diagnostic::new(format!("schema path not found: {}", schema_dir.display()))
+        .with_label(schema_path.span(), "no such directory")
+}
+
+fn toml_diagnostic(message: &str, span: Option<Range<usize>>) -> Diagnostic {
+    let diagnostic = Diagnostic::new("invalid crunch file");
+    match span {
+        Some(span) => diagnostic.with_label(span, message.trim()),
+        None => Diagnostic::new(message.trim()),
+    }
+}
+
+/// Packages declared by the proto files in `dir`, unreadable files are left to protoc to report
+fn proto_packages(dir: &Path) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut dirs = vec![PathBuf::from(dir)];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().and_then(|e| e.to_str()) == Some("proto") {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                packages.extend(content.lines().find_map(|line| {
+                    line.trim()
+                        .strip_prefix("package ")
+                        .map(|p| p.trim().trim_end_matches(';').trim().to_string())
+                }));
+            }
+        }
+    }
+
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(diagnostics: &[Diagnostic], content: &str) -> Vec<(String, Vec<String>)> {
+        diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.message.clone(),
+                    d.labels
+                        .iter()
+                        .map(|l| content[l.span.clone()].to_string())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_validate() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(dir.path().join("schemas/crunch"))?;
+        std::fs::write(
+            dir.path().join("schemas/crunch/user.proto"),
+            "syntax = \"proto3\";\n\npackage my_domain.user;\n",
+        )?;
+
+        let raw = r#"[service]
+service = "My-Service"
+domain = "my-domain"
+codegen = ["rust", "java"]
+
+[[publish]]
+schema-path = "schemas/crunch"
+output-path = "src/gencrunch"
+entities = ["user", "order"]
+
+[[publish]]
+schema-path = "schemas/crunch"
+output-path = "src/other"
+entities = ["user"]
+
+[[publish]]
+schema-path = "schemas/missing"
+output-path = "src/gencrunch"
+entities = []
+
+[[subscription]]
+service = "other-service"
+domain = "other-domain"
+version = "1.0.0"
+output-path = "src/gencrunch_other"
+"#;
+
+        let file = File::parse(raw).await?;
+        let content = file.write().await?;
+        let diagnostics = file.validate(dir.path());
+
+        pretty_assertions::assert_eq!(
+            messages(&diagnostics, &content),
+            vec![
+                (
+                    "invalid service name: \"My-Service\"".to_string(),
+                    vec!["\"My-Service\"".to_string()]
+                ),
+                (
+                    "unknown codegen target: java".into(),
+                    vec!["\"java\"".into()]
+                ),
+                (
+                    "entity: order has no schema in: schemas/crunch".into(),
+                    vec!["\"order\"".into()]
+                ),
+                (
+                    "schema path: schemas/crunch is published more than once".into(),
+                    vec!["\"schemas/crunch\"".into(), "\"schemas/crunch\"".into()]
+                ),
+                (
+                    "output path: src/gencrunch is used more than once".into(),
+                    vec!["\"src/gencrunch\"".into(), "\"src/gencrunch\"".into()]
+                ),
+                (
+                    format!(
+                        "schema path not found: {}",
+                        dir.path().join("schemas/missing").display()
+                    ),
+                    vec!["\"schemas/missing\"".into()]
+                ),
+                (
+                    "no schema source for subscription: other-domain/other-service".into(),
+                    vec!["\"other-service\"".into()]
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_structure() -> anyhow::Result<()> {
+        let raw = r#"[service]
+service = "my-service"
+domain = "my-domain"
+codegen = "rust"
+"#;
+
+        let diagnostics = File::parse(raw).await?.validate(Path::new("."));
+
+        pretty_assertions::assert_eq!(
+            messages(&diagnostics, raw),
+            vec![(
+                "invalid crunch file".to_string(),
+                vec!["\"rust\"".to_string()]
+            )]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::TransportError, EventInfo, PartitionKey, Transport};
+use futures::Stream;
+use rdkafka::{
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    message::{Header, Headers, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig, Message, Offset, TopicPartitionList,
+};
+
+const ENTITY_HEADER: &str = "crunch-entity";
+const EVENT_HEADER: &str = "crunch-event";
+const COMMIT_INTERVAL: Duration = Duration::from_secs(5);
+
+type KeyFn = Arc<dyn Fn(&[u8]) -> Option<String> + Send + Sync>;
+
+/// How events map to topics, events sharing a topic are told apart by their headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicLayout {
+    /// `crunch.{domain}`, keeps the order between every event of the domain with the same key
+    Domain,
+    /// `crunch.{domain}.{entity}`
+    Entity,
+}
+
+#[derive(Clone)]
+pub struct KafkaTransportOptions {
+    /// Comma separated list of brokers
+    pub brokers: String,
+    /// Consumer group of the subscribing service, instances of a service share it and split the partitions between
+    /// them
+    pub group: String,
+    pub topic_layout: TopicLayout,
+    /// New consumer groups start at the earliest event still in the topic, instead of the latest
+    pub from_beginning: bool,
+    /// How long publishing waits for the event to be acknowledged by the brokers
+    pub send_timeout: Duration,
+    partition_keys: HashMap<EventInfo, KeyFn>,
+}
+
+impl KafkaTransportOptions {
+    pub fn new(brokers: impl Into<String>, group: impl Into<String>) -> Self {
+        Self {
+            brokers: brokers.into(),
+            group: group.into(),
+            topic_layout: TopicLayout::Domain,
+            from_beginning: false,
+            send_timeout: Duration::from_secs(30),
+            partition_keys: HashMap::new(),
+        }
+    }
+
+    /// Publishes `T` with its [`PartitionKey`], such that events of the same entity keep their order. The transport
+    /// only sees the serialized event, so it is deserialized to find the key. Events without a key are spread over the
+    /// partitions
+    pub fn with_partition_key<T>(mut self) -> Self
+    where
+        T: PartitionKey + 'static,
+    {
+        self.partition_keys.insert(
+            T::event_info(),
+            Arc::new(|content: &[u8]| match T::deserialize(content.to_vec()) {
+                Ok(event) => Some(event.partition_key()),
+                Err(e) => {
+                    tracing::warn!("failed to deserialize event for its partition key: {}", e);
+                    None
+                }
+            }),
+        );
+        self
+    }
+}
+
+impl std::fmt::Debug for KafkaTransportOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaTransportOptions")
+            .field("brokers", &self.brokers)
+            .field("group", &self.group)
+            .field("topic_layout", &self.topic_layout)
+            .field("from_beginning", &self.from_beginning)
+            .field("send_timeout", &self.send_timeout)
+            .field(
+                "partition_keys",
+                &self.partition_keys.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// A transport on kafka, or a kafka compatible broker such as redpanda.
+///
+/// The producer is idempotent, and subscriptions commit the offset of an event once the next one is asked for
+#[derive(Clone)]
+pub struct KafkaTransport {
+    producer: FutureProducer,
+    options: KafkaTransportOptions,
+}
+
+impl KafkaTransport {
+    pub fn new(options: KafkaTransportOptions) -> Result<Self, TransportError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &options.brokers)
+            .set("enable.idempotence", "true")
+            .set("acks", "all")
+            .create()
+            .map_err(|e| anyhow::anyhow!("failed to create kafka producer: {}", e))
+            .map_err(TransportError::Err)?;
+
+        Ok(Self { producer, options })
+    }
+
+    fn topic(&self, event_info: &EventInfo) -> String {
+        match self.options.topic_layout {
+            TopicLayout::Domain => format!("crunch.{}", event_info.domain),
+            TopicLayout::Entity => {
+                format!("crunch.{}.{}", event_info.domain, event_info.entity_type)
+            }
+        }
+    }
+
+    async fn publish_event(&self, event_info: &EventInfo, content: Vec<u8>) -> anyhow::Result<()> {
+        let topic = self.topic(event_info);
+        let key = self
+            .options
+            .partition_keys
+            .get(event_info)
+            .and_then(|key| key(&content));
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: ENTITY_HEADER,
+                value: Some(&event_info.entity_type),
+            })
+            .insert(Header {
+                key: EVENT_HEADER,
+                value: Some(&event_info.event_name),
+            });
+
+        let mut record = FutureRecord::to(&topic).payload(&content).headers(headers);
+        if let Some(key) = &key {
+            record = record.key(key);
+        }
+
+        self.producer
+            .send(record, self.options.send_timeout)
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("failed to publish to: {}: {}", topic, e))?;
+
+        Ok(())
+    }
+
+    fn subscribe(&self, event_info: &EventInfo) -> anyhow::Result<Subscription> {
+        // Events share topics, each event has its own group such that every subscription sees all partitions
+        let group = format!(
+            "{}.{}.{}.{}",
+            self.options.group, event_info.domain, event_info.entity_type, event_info.event_name
+        );
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &self.options.brokers)
+            .set("group.id", &group)
+            .set("enable.auto.commit", "false")
+            .set(
+                "auto.offset.reset",
+                if self.options.from_beginning {
+                    "earliest"
+                } else {
+                    "latest"
+                },
+            )
+            .create()?;
+        consumer.subscribe(&[&self.topic(event_info)])?;
+
+        Ok(Subscription {
+            consumer,
+            event_info: event_info.clone(),
+            uncommitted: None,
+            skipped: HashMap::new(),
+            last_commit: Instant::now(),
+        })
+    }
+}
+
+struct Subscription {
+    consumer: StreamConsumer,
+    event_info: EventInfo,
+    /// Topic, partition and offset of the event handed out last, it is committed when the next one is asked for
+    uncommitted: Option<(String, i32, i64)>,
+    /// Offset of the last skipped event per topic and partition, committed every [`COMMIT_INTERVAL`] such that a
+    /// group whose events are rare doesn't fall behind on the other events of the topic
+    skipped: HashMap<(String, i32), i64>,
+    last_commit: Instant,
+}
+
+impl Subscription {
+    async fn next(&mut self) -> anyhow::Result<Vec<u8>> {
+        self.commit()?;
+
+        loop {
+            let message = match tokio::time::timeout(COMMIT_INTERVAL, self.consumer.recv()).await {
+                Ok(message) => message?,
+                Err(_) => {
+                    self.commit()?;
+                    continue;
+                }
+            };
+            // Other events of the topic are skipped
+            if !self.matches(&message) {
+                let (topic, partition, offset) = (
+                    message.topic().to_string(),
+                    message.partition(),
+                    message.offset(),
+                );
+                self.skipped.insert((topic, partition), offset);
+                if self.last_commit.elapsed() >= COMMIT_INTERVAL {
+                    self.commit()?;
+                }
+                continue;
+            }
+
+            self.uncommitted = Some((
+                message.topic().to_string(),
+                message.partition(),
+                message.offset(),
+            ));
+            return Ok(message.payload().unwrap_or_default().to_vec());
+        }
+    }
+
+    /// Commits the event handed out last and the skipped events, the next event of a partition is the one after
+    fn commit(&mut self) -> anyhow::Result<()> {
+        let mut positions = std::mem::take(&mut self.skipped);
+        if let Some((topic, partition, offset)) = self.uncommitted.take() {
+            let position = positions.entry((topic, partition)).or_insert(offset);
+            *position = (*position).max(offset);
+        }
+        self.last_commit = Instant::now();
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let mut offsets = TopicPartitionList::new();
+        for ((topic, partition), offset) in positions {
+            offsets.add_partition_offset(&topic, partition, Offset::Offset(offset + 1))?;
+        }
+        self.consumer.commit(&offsets, CommitMode::Async)?;
+
+        Ok(())
+    }
+
+    fn matches(&self, message: &impl Message) -> bool {
+        let Some(headers) = message.headers() else {
+            return false;
+        };
+        let header = |key: &str| headers.iter().find(|h| h.key == key).and_then(|h| h.value);
+
+        header(ENTITY_HEADER) == Some(self.event_info.entity_type.as_bytes())
+            && header(EVENT_HEADER) == Some(self.event_info.event_name.as_bytes())
+    }
+}
+
+#[async_trait]
+impl Transport for KafkaTransport {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        self.publish_event(event_info, content)
+            .await
+            .map_err(TransportError::Err)
+    }
+
+    async fn subscriber(
+        &self,
+        event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        let subscription = self.subscribe(event_info).map_err(TransportError::Err)?;
+
+        let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+            loop {
+                match subscription.next().await {
+                    Ok(content) => {
+                        tracing::trace!("got event from kafka");
+                        return Some((content, subscription));
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to receive event from kafka: {e}");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Box::pin(stream)))
+    }
+}
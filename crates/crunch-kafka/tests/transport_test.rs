@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use crunch_kafka::{KafkaTransport, KafkaTransportOptions, TopicLayout};
+use crunch_traits::{
+    errors::{DeserializeError, SerializeError},
+    testing::{broker_url, event_info, next_event, publish_subscribe},
+    Deserializer, Event, EventInfo, PartitionKey, Serializer, Transport,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn options(brokers: &str, group: &str) -> KafkaTransportOptions {
+    let mut options = KafkaTransportOptions::new(brokers, group);
+    // Events have unique names per test, so starting at the beginning only replays the events of the test
+    options.from_beginning = true;
+    options
+}
+
+struct OrderEvent {
+    order_id: String,
+    sequence: u32,
+}
+
+impl Serializer for OrderEvent {
+    fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
+        Ok(format!("{}:{}", self.order_id, self.sequence).into_bytes())
+    }
+}
+
+impl Deserializer for OrderEvent {
+    fn deserialize(raw: Vec<u8>) -> Result<Self, DeserializeError> {
+        let parse = || {
+            let raw = String::from_utf8(raw)?;
+            let (order_id, sequence) = raw.split_once(':').ok_or(anyhow::anyhow!("missing ':'"))?;
+
+            anyhow::Ok(Self {
+                order_id: order_id.into(),
+                sequence: sequence.parse()?,
+            })
+        };
+
+        parse().map_err(DeserializeError::FailedToDeserialize)
+    }
+}
+
+impl Event for OrderEvent {
+    fn event_info() -> EventInfo {
+        EventInfo {
+            domain: "some-domain".into(),
+            entity_type: "order".into(),
+            event_name: "kafka-test-order-updated".into(),
+        }
+    }
+}
+
+impl PartitionKey for OrderEvent {
+    fn partition_key(&self) -> String {
+        self.order_id.clone()
+    }
+}
+
+#[test]
+fn test_options_debug_lists_partition_keys() {
+    let options = KafkaTransportOptions::new("127.0.0.1:9092", "some-group")
+        .with_partition_key::<OrderEvent>();
+
+    assert!(format!("{:?}", options).contains("kafka-test-order-updated"));
+}
+
+#[tokio::test]
+async fn test_transport_publish_subscribe() -> anyhow::Result<()> {
+    let Some(brokers) = broker_url("KAFKA_BROKERS") else {
+        return Ok(());
+    };
+
+    publish_subscribe(
+        &KafkaTransport::new(options(&brokers, "test-publish-subscribe"))?,
+        TIMEOUT,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_transport_skips_other_events_of_topic() -> anyhow::Result<()> {
+    let Some(brokers) = broker_url("KAFKA_BROKERS") else {
+        return Ok(());
+    };
+    let mut options = options(&brokers, "test-skips-other-events");
+    options.topic_layout = TopicLayout::Entity;
+    let transport = KafkaTransport::new(options)?;
+    let event_info = event_info();
+    let other = self::event_info();
+
+    let mut stream = transport.subscriber(&event_info).await?.unwrap();
+    transport.publish(&other, b"other".to_vec()).await?;
+    transport.publish(&event_info, b"event".to_vec()).await?;
+
+    assert_eq!(b"event".to_vec(), next_event(&mut stream, TIMEOUT).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transport_keeps_order_per_partition_key() -> anyhow::Result<()> {
+    let Some(brokers) = broker_url("KAFKA_BROKERS") else {
+        return Ok(());
+    };
+    let transport = KafkaTransport::new(
+        options(&brokers, "test-partition-key").with_partition_key::<OrderEvent>(),
+    )?;
+    let order_id = uuid::Uuid::new_v4().to_string();
+
+    let mut stream = transport
+        .subscriber(&OrderEvent::event_info())
+        .await?
+        .unwrap();
+    for sequence in 0..10 {
+        let event = OrderEvent {
+            order_id: order_id.clone(),
+            sequence,
+        };
+        transport
+            .publish(&OrderEvent::event_info(), event.serialize()?)
+            .await?;
+    }
+
+    // The topic may hold events of earlier runs, which are skipped
+    let mut sequences = Vec::new();
+    while sequences.len() < 10 {
+        let event = OrderEvent::deserialize(next_event(&mut stream, TIMEOUT).await?)?;
+        if event.order_id == order_id {
+            sequences.push(event.sequence);
+        }
+    }
+    assert_eq!((0..10).collect::<Vec<_>>(), sequences);
+
+    Ok(())
+}
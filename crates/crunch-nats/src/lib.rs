@@ -9,7 +9,12 @@ pub struct NatsConnectOptions<'a> {
     pub credentials: NatsConnectCredentials<'a>,
 }
 pub enum NatsConnectCredentials<'a> {
-    UserPass { user: &'a str, pass: &'a str },
+    UserPass {
+        user: &'a str,
+        pass: &'a str,
+    },
+    /// Connect without authenticating, as to a local nats server
+    None,
 }
 
 #[derive(Clone)]
@@ -27,6 +32,11 @@ impl NatsTransport {
                     .map_err(|e| anyhow::anyhow!("failed to connect with username password: {}", e))
                     .map_err(TransportError::Err)?
             }
+            NatsConnectCredentials::None => nats::asynk::Options::new()
+                .connect(options.host)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to connect: {}", e))
+                .map_err(TransportError::Err)?,
         };
 
         Ok(Self { conn })
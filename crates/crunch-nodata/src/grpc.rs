@@ -1 +1,3 @@
+#![allow(dead_code, clippy::empty_docs)]
+
 include!("gen/nodata.v1.rs");
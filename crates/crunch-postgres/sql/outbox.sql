@@ -1,5 +1,4 @@
--- Add migration script here
-CREATE TABLE outbox (
+CREATE TABLE IF NOT EXISTS "outbox" (
     id UUID NOT NULL,
     metadata JSONB NOT NULL,
     content BYTEA NOT NULL,
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use crunch_traits::EventInfo;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{OutboxEvent, PgEventInfo, PostgresPersistence};
+
+/// State of an event in the outbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxState {
+    /// Waiting to be published by the relay
+    Inserted,
+    Handled,
+    Failed,
+}
+
+impl OutboxState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxState::Inserted => "inserted",
+            OutboxState::Handled => "handled",
+            OutboxState::Failed => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for OutboxState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An event in the outbox, as seen by the outbox administration
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub event_info: EventInfo,
+    pub content: Vec<u8>,
+    pub inserted_time: chrono::DateTime<chrono::Utc>,
+    pub state: String,
+}
+
+impl From<OutboxEvent> for OutboxEntry {
+    fn from(value: OutboxEvent) -> Self {
+        let Json(metadata): Json<PgEventInfo> = value.metadata;
+
+        Self {
+            id: value.id,
+            event_info: metadata.into(),
+            content: value.content,
+            inserted_time: value.inserted_time,
+            state: value.state,
+        }
+    }
+}
+
+impl PostgresPersistence {
+    /// Events in `state`, oldest first
+    pub async fn list(&self, state: OutboxState, limit: i64) -> anyhow::Result<Vec<OutboxEntry>> {
+        let events = sqlx::query_as::<_, OutboxEvent>(&format!(
+            r#"
+SELECT *
+FROM {table}
+WHERE state = $1
+ORDER BY inserted_time ASC
+LIMIT $2;
+"#,
+            table = self.table
+        ))
+        .bind(state.as_str())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events.into_iter().map(OutboxEntry::from).collect())
+    }
+
+    pub async fn show(&self, id: Uuid) -> anyhow::Result<Option<OutboxEntry>> {
+        let event = sqlx::query_as::<_, OutboxEvent>(&format!(
+            "SELECT * from {} where id = $1",
+            self.table
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(event.map(OutboxEntry::from))
+    }
+
+    /// Marks the event as inserted again, such that the relay publishes it once more. Returns whether it existed
+    pub async fn requeue(&self, id: Uuid) -> anyhow::Result<bool> {
+        let result = sqlx::query(&format!(
+            r#"
+UPDATE {table}
+SET state = 'inserted'
+WHERE id = $1;
+"#,
+            table = self.table
+        ))
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Requeues every failed event, returns how many there were.
+    ///
+    /// Nothing in crunch marks events as failed yet, the relay retries them as inserted instead. This only requeues
+    /// events marked failed by hand or by other tools, and is otherwise a no-op
+    pub async fn requeue_failed(&self) -> anyhow::Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+UPDATE {table}
+SET state = 'inserted'
+WHERE state = 'failed';
+"#,
+            table = self.table
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes the events in `state` inserted longer ago than `older_than`, returns how many were deleted
+    pub async fn purge(&self, state: OutboxState, older_than: Duration) -> anyhow::Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+DELETE FROM {table}
+WHERE state = $1
+AND inserted_time < now() - make_interval(secs => $2);
+"#,
+            table = self.table
+        ))
+        .bind(state.as_str())
+        .bind(older_than.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
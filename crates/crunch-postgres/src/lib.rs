@@ -1,24 +1,101 @@
 use async_trait::async_trait;
 use crunch_traits::{errors::PersistenceError, EventInfo};
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPoolOptions, types::Json, Pool, Postgres};
+use sqlx::{postgres::PgPoolOptions, types::Json, Executor, Pool, Postgres};
 use uuid::Uuid;
 
+mod admin;
+mod transport;
+pub use admin::{OutboxEntry, OutboxState};
+pub use transport::{PostgresTransport, PostgresTransportOptions};
+
 pub struct PostgresTx {}
 
 impl crunch_traits::Tx for PostgresTx {}
 
+/// Where the outbox lives, and whether it is migrated by crunch
+#[derive(Debug, Clone)]
+pub struct PostgresOptions {
+    /// Schema holding the outbox table, defaults to the search path of the connection. Give each tenant of a
+    /// database its own schema to keep their outboxes apart
+    pub schema: Option<String>,
+    pub table: String,
+    /// Creates the schema and table if they are missing. Turn it off to apply [`PostgresPersistence::migration_sql`]
+    /// through your own migration tool instead
+    pub run_migrations: bool,
+}
+
+impl Default for PostgresOptions {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            table: "outbox".into(),
+            run_migrations: true,
+        }
+    }
+}
+
+impl PostgresOptions {
+    /// The quoted, schema qualified name of the table
+    fn qualified_table(&self) -> anyhow::Result<String> {
+        let table = quote_identifier(&self.table)?;
+
+        match &self.schema {
+            Some(schema) => Ok(format!("{}.{}", quote_identifier(schema)?, table)),
+            None => Ok(table),
+        }
+    }
+}
+
+// Identifiers can't be bound as parameters, so only plain ones are allowed into the queries
+fn quote_identifier(identifier: &str) -> anyhow::Result<String> {
+    let valid = identifier
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        anyhow::bail!(
+            "invalid postgres identifier: {}, it can only contain letters, numbers and _",
+            identifier
+        );
+    }
+
+    Ok(format!("\"{}\"", identifier))
+}
+
 pub struct PostgresPersistence {
     pool: Pool<Postgres>,
+    table: String,
 }
 
 impl PostgresPersistence {
     pub async fn new(dsn: &str) -> anyhow::Result<Self> {
         let pool = PgPoolOptions::new().max_connections(5).connect(dsn).await?;
 
-        sqlx::migrate!().run(&pool).await?;
+        Self::new_from_pool(pool).await
+    }
+
+    /// Uses the pool of the application instead of creating one. Runs the migrations
+    pub async fn new_from_pool(pool: Pool<Postgres>) -> anyhow::Result<Self> {
+        Self::new_with_options(pool, PostgresOptions::default()).await
+    }
 
-        Ok(Self { pool })
+    pub async fn new_with_options(
+        pool: Pool<Postgres>,
+        options: PostgresOptions,
+    ) -> anyhow::Result<Self> {
+        if options.run_migrations {
+            pool.execute(Self::migration_sql(&options)?.as_str())
+                .await?;
+        }
+
+        Ok(Self {
+            pool,
+            table: options.qualified_table()?,
+        })
     }
 
     pub async fn new_from_env() -> anyhow::Result<Self> {
@@ -27,6 +104,30 @@ impl PostgresPersistence {
 
         Self::new(&dsn).await
     }
+
+    /// The sql creating the outbox for `options`, safe to run more than once. For the default options it is shipped as
+    /// `sql/outbox.sql`
+    pub fn migration_sql(options: &PostgresOptions) -> anyhow::Result<String> {
+        let schema = match &options.schema {
+            Some(schema) => format!(
+                "CREATE SCHEMA IF NOT EXISTS {};\n",
+                quote_identifier(schema)?
+            ),
+            None => String::new(),
+        };
+
+        Ok(format!(
+            r#"{schema}CREATE TABLE IF NOT EXISTS {table} (
+    id UUID NOT NULL,
+    metadata JSONB NOT NULL,
+    content BYTEA NOT NULL,
+    inserted_time TIMESTAMPTZ NOT NULL DEFAULT now(),
+    state VARCHAR NOT NULL
+);
+"#,
+            table = options.qualified_table()?
+        ))
+    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -79,9 +180,9 @@ impl crunch_traits::Persistence for PostgresPersistence {
     // This should be solved by adding transactions, event streams and sequence numbers
     async fn insert(&self, event_info: &EventInfo, content: Vec<u8>) -> anyhow::Result<()> {
         let event_info: PgEventInfo = event_info.into();
-        sqlx::query_as::<_, InsertResp>(
+        sqlx::query_as::<_, InsertResp>(&format!(
             r#"
-INSERT INTO outbox (id, metadata, content, state) 
+INSERT INTO {table} (id, metadata, content, state) 
 VALUES (
     $1, 
     $2, 
@@ -90,7 +191,8 @@ VALUES (
 ) 
 RETURNING id;
 "#,
-        )
+            table = self.table
+        ))
         .bind(uuid::Uuid::new_v4())
         .bind(Json(&event_info))
         .bind(content)
@@ -100,16 +202,17 @@ RETURNING id;
         Ok(())
     }
     async fn next(&self) -> Result<Option<(String, crunch_traits::DynTx)>, PersistenceError> {
-        let resp = sqlx::query_as::<_, InsertResp>(
+        let resp = sqlx::query_as::<_, InsertResp>(&format!(
             r#"
 SELECT id 
-FROM outbox 
+FROM {table} 
 WHERE state = 'inserted' 
 ORDER BY inserted_time ASC 
 LIMIT 1 
 FOR UPDATE;
 "#,
-        )
+            table = self.table
+        ))
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| anyhow::anyhow!(e))
@@ -120,16 +223,19 @@ FOR UPDATE;
         Ok(id.map(|id| (id, Box::new(PostgresTx {}) as crunch_traits::DynTx)))
     }
     async fn get(&self, event_id: &str) -> Result<Option<(EventInfo, Vec<u8>)>, PersistenceError> {
-        let event = sqlx::query_as::<_, OutboxEvent>("SELECT * from outbox where id = $1")
-            .bind(
-                Uuid::parse_str(event_id)
-                    .map_err(|e| anyhow::anyhow!(e))
-                    .map_err(PersistenceError::GetErr)?,
-            )
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| anyhow::anyhow!(e))
-            .map_err(PersistenceError::GetErr)?;
+        let event = sqlx::query_as::<_, OutboxEvent>(&format!(
+            "SELECT * from {} where id = $1",
+            self.table
+        ))
+        .bind(
+            Uuid::parse_str(event_id)
+                .map_err(|e| anyhow::anyhow!(e))
+                .map_err(PersistenceError::GetErr)?,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map_err(PersistenceError::GetErr)?;
 
         match event {
             Some(event) => {
@@ -141,13 +247,14 @@ FOR UPDATE;
         }
     }
     async fn update_published(&self, event_id: &str) -> Result<(), PersistenceError> {
-        sqlx::query(
+        sqlx::query(&format!(
             r#"
-UPDATE outbox
+UPDATE {table}
 SET state = 'handled'
 WHERE id = $1;
 "#,
-        )
+            table = self.table
+        ))
         .bind(
             Uuid::parse_str(event_id)
                 .map_err(|e| anyhow::anyhow!(e))
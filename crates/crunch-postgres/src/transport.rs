@@ -0,0 +1,388 @@
+use std::{collections::VecDeque, pin::Pin, time::Duration};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::TransportError, EventInfo, Transport};
+use futures::Stream;
+use sqlx::{
+    postgres::{PgConnection, PgListener},
+    Executor, Pool, Postgres,
+};
+
+use crate::quote_identifier;
+
+const CHANNEL: &str = "crunch_events";
+const BATCH_SIZE: i64 = 100;
+
+/// Where the events live, and who is consuming them
+#[derive(Debug, Clone)]
+pub struct PostgresTransportOptions {
+    /// Schema holding the events and consumer offsets tables, defaults to the search path of the connection
+    pub schema: Option<String>,
+    /// Name of this consumer, each consumer receives every event once and continues where it left off after a restart.
+    /// Instances of a service should share it, they take turns through a lock per topic: one instance reads the
+    /// events of a topic while the others wait to take over
+    pub consumer: String,
+    /// How often subscriptions look for events when no notification arrives, in case one was missed
+    pub poll_interval: Duration,
+    /// Creates the schema and tables if they are missing, see [`PostgresTransport::migration_sql`]
+    pub run_migrations: bool,
+}
+
+impl PostgresTransportOptions {
+    pub fn new(consumer: impl Into<String>) -> Self {
+        Self {
+            schema: None,
+            consumer: consumer.into(),
+            poll_interval: Duration::from_secs(5),
+            run_migrations: true,
+        }
+    }
+
+    fn qualified_table(&self, table: &str) -> anyhow::Result<String> {
+        let table = quote_identifier(table)?;
+
+        match &self.schema {
+            Some(schema) => Ok(format!("{}.{}", quote_identifier(schema)?, table)),
+            None => Ok(table),
+        }
+    }
+}
+
+/// A transport on top of an append only events table, for deployments with nothing but postgres.
+///
+/// Subscriptions read the events after the offset of their consumer, and are woken up by LISTEN/NOTIFY as events are
+/// published
+#[derive(Clone)]
+pub struct PostgresTransport {
+    pool: Pool<Postgres>,
+    events_table: String,
+    offsets_table: String,
+    consumer: String,
+    poll_interval: Duration,
+}
+
+impl PostgresTransport {
+    pub async fn new(
+        pool: Pool<Postgres>,
+        options: PostgresTransportOptions,
+    ) -> anyhow::Result<Self> {
+        if options.run_migrations {
+            pool.execute(Self::migration_sql(&options)?.as_str())
+                .await?;
+        }
+
+        Ok(Self {
+            pool,
+            events_table: options.qualified_table("crunch_events")?,
+            offsets_table: options.qualified_table("crunch_consumer_offsets")?,
+            consumer: options.consumer,
+            poll_interval: options.poll_interval,
+        })
+    }
+
+    /// The sql creating the events and consumer offsets tables, safe to run more than once
+    pub fn migration_sql(options: &PostgresTransportOptions) -> anyhow::Result<String> {
+        let schema = match &options.schema {
+            Some(schema) => format!(
+                "CREATE SCHEMA IF NOT EXISTS {};\n",
+                quote_identifier(schema)?
+            ),
+            None => String::new(),
+        };
+
+        Ok(format!(
+            r#"{schema}CREATE TABLE IF NOT EXISTS {events} (
+    sequence BIGSERIAL PRIMARY KEY,
+    topic VARCHAR NOT NULL,
+    content BYTEA NOT NULL,
+    inserted_time TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS crunch_events_topic_sequence ON {events} (topic, sequence);
+CREATE TABLE IF NOT EXISTS {offsets} (
+    consumer VARCHAR NOT NULL,
+    topic VARCHAR NOT NULL,
+    sequence BIGINT NOT NULL,
+    PRIMARY KEY (consumer, topic)
+);
+"#,
+            events = options.qualified_table("crunch_events")?,
+            offsets = options.qualified_table("crunch_consumer_offsets")?,
+        ))
+    }
+
+    async fn publish_event(&self, topic: &str, content: Vec<u8>) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // Sequences are handed out before commit, serializing publishers keeps a consumer from reading past an event
+        // which commits after a later one. Consumers read per topic, so only publishers of the same topic wait on
+        // each other
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1), hashtext($2));")
+            .bind(&self.events_table)
+            .bind(topic)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT INTO {} (topic, content) VALUES ($1, $2);",
+            self.events_table
+        ))
+        .bind(topic)
+        .bind(content)
+        .execute(&mut *tx)
+        .await?;
+        // Notifications are delivered on commit
+        sqlx::query("SELECT pg_notify($1, $2);")
+            .bind(CHANNEL)
+            .bind(topic)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// A connection holding the lock of the consumer on `topic`, or None when another instance holds it. The lock is
+    /// held for as long as the connection lives, such that an instance which dies hands it over
+    async fn lock(&self, topic: &str) -> anyhow::Result<Option<PgConnection>> {
+        let mut conn = self.pool.acquire().await?.detach();
+
+        let (locked,): (bool,) =
+            sqlx::query_as("SELECT pg_try_advisory_lock(hashtext($1), hashtext($2));")
+                .bind(format!("{}.{}", self.offsets_table, self.consumer))
+                .bind(topic)
+                .fetch_one(&mut conn)
+                .await?;
+
+        Ok(locked.then_some(conn))
+    }
+
+    /// The offset of the consumer, new consumers start at the latest event
+    async fn offset(&self, conn: &mut PgConnection, topic: &str) -> anyhow::Result<i64> {
+        let offset: Option<(i64,)> = sqlx::query_as(&format!(
+            "SELECT sequence FROM {} WHERE consumer = $1 AND topic = $2;",
+            self.offsets_table
+        ))
+        .bind(&self.consumer)
+        .bind(topic)
+        .fetch_optional(&mut *conn)
+        .await?;
+        if let Some((offset,)) = offset {
+            return Ok(offset);
+        }
+
+        let (latest,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COALESCE(MAX(sequence), 0) FROM {} WHERE topic = $1;",
+            self.events_table
+        ))
+        .bind(topic)
+        .fetch_one(&mut *conn)
+        .await?;
+        self.commit(conn, topic, latest).await?;
+
+        Ok(latest)
+    }
+
+    async fn commit(
+        &self,
+        conn: &mut PgConnection,
+        topic: &str,
+        sequence: i64,
+    ) -> anyhow::Result<()> {
+        // An instance which lost its lock without noticing mustn't move the offset back
+        sqlx::query(&format!(
+            r#"
+INSERT INTO {offsets} (consumer, topic, sequence)
+VALUES ($1, $2, $3)
+ON CONFLICT (consumer, topic) DO UPDATE SET sequence = GREATEST({offsets}.sequence, EXCLUDED.sequence);
+"#,
+            offsets = self.offsets_table
+        ))
+        .bind(&self.consumer)
+        .bind(topic)
+        .bind(sequence)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        conn: &mut PgConnection,
+        topic: &str,
+        after: i64,
+    ) -> anyhow::Result<Vec<(i64, Vec<u8>)>> {
+        let events = sqlx::query_as(&format!(
+            r#"
+SELECT sequence, content
+FROM {}
+WHERE topic = $1 AND sequence > $2
+ORDER BY sequence ASC
+LIMIT $3;
+"#,
+            self.events_table
+        ))
+        .bind(topic)
+        .bind(after)
+        .bind(BATCH_SIZE)
+        .fetch_all(conn)
+        .await?;
+
+        Ok(events)
+    }
+}
+
+struct Subscription {
+    transport: PostgresTransport,
+    listener: PgListener,
+    topic: String,
+    /// Holds the lock of the consumer on the topic, events are only read and committed through it
+    lock: Option<PgConnection>,
+    /// Sequence of the last event handed out
+    offset: i64,
+    /// Sequence of the last event committed, events are committed once the next one is asked for
+    committed: i64,
+    buffer: VecDeque<(i64, Vec<u8>)>,
+}
+
+impl Subscription {
+    /// Takes the lock of the consumer on the topic, returns whether it got it
+    async fn acquire(&mut self) -> anyhow::Result<bool> {
+        let Some(mut conn) = self.transport.lock(&self.topic).await? else {
+            return Ok(false);
+        };
+
+        // Another instance may have moved the offset while this one waited
+        self.offset = self.transport.offset(&mut conn, &self.topic).await?;
+        self.committed = self.offset;
+        self.buffer.clear();
+        self.lock = Some(conn);
+
+        Ok(true)
+    }
+
+    async fn next(&mut self) -> anyhow::Result<Vec<u8>> {
+        loop {
+            // The lock is given up on errors, such that a broken connection is replaced
+            let Some(mut conn) = self.lock.take() else {
+                if !self.acquire().await? {
+                    tracing::trace!(
+                        "another instance of: {} holds: {}, waiting",
+                        self.transport.consumer,
+                        self.topic
+                    );
+                    tokio::time::sleep(self.transport.poll_interval).await;
+                }
+                continue;
+            };
+            let content = self.read(&mut conn).await?;
+            self.lock = Some(conn);
+            if let Some(content) = content {
+                return Ok(content);
+            }
+
+            // A timeout just means looking again, in case a notification was missed
+            match tokio::time::timeout(self.transport.poll_interval, self.listener.recv()).await {
+                Ok(notification) => {
+                    notification?;
+                }
+                Err(_) => {
+                    tracing::trace!("no notification for: {}, polling", self.topic);
+                }
+            }
+        }
+    }
+
+    async fn read(&mut self, conn: &mut PgConnection) -> anyhow::Result<Option<Vec<u8>>> {
+        if self.committed < self.offset {
+            self.transport
+                .commit(conn, &self.topic, self.offset)
+                .await?;
+            self.committed = self.offset;
+        }
+
+        if self.buffer.is_empty() {
+            self.buffer
+                .extend(self.transport.fetch(conn, &self.topic, self.offset).await?);
+        }
+
+        Ok(self.buffer.pop_front().map(|(sequence, content)| {
+            self.offset = sequence;
+            content
+        }))
+    }
+}
+
+#[async_trait]
+impl Transport for PostgresTransport {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        self.publish_event(&event_info.transport_name(), content)
+            .await
+            .map_err(TransportError::Err)
+    }
+
+    async fn subscriber(
+        &self,
+        event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        let topic = event_info.transport_name();
+
+        let subscription = async {
+            let mut listener = PgListener::connect_with(&self.pool).await?;
+            listener.listen(CHANNEL).await?;
+
+            let mut subscription = Subscription {
+                transport: self.clone(),
+                listener,
+                topic: topic.clone(),
+                lock: None,
+                offset: 0,
+                committed: 0,
+                buffer: VecDeque::new(),
+            };
+            // New consumers start at the latest event as of subscribing, when another instance holds the lock the
+            // offset already exists
+            subscription.acquire().await?;
+
+            anyhow::Ok(subscription)
+        }
+        .await
+        .map_err(TransportError::Err)?;
+
+        let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+            loop {
+                match subscription.next().await {
+                    Ok(content) => {
+                        tracing::trace!("got event from postgres");
+                        return Some((content, subscription));
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to receive event from postgres: {e}");
+                        tokio::time::sleep(subscription.transport.poll_interval).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Box::pin(stream)))
+    }
+}
+
+trait EventInfoExt {
+    fn transport_name(&self) -> String;
+}
+
+impl EventInfoExt for EventInfo {
+    fn transport_name(&self) -> String {
+        format!(
+            "crunch.{}.{}.{}",
+            self.domain, self.entity_type, self.event_name
+        )
+    }
+}
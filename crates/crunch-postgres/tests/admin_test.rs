@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use crunch_postgres::{OutboxState, PostgresOptions, PostgresPersistence};
+use crunch_traits::{EventInfo, Persistence};
+use sqlx::postgres::PgPoolOptions;
+
+// Every test gets its own schema, such that purging doesn't delete the events of other tests
+async fn persistence(schema: &str) -> anyhow::Result<PostgresPersistence> {
+    let dsn = std::env::var("DATABASE_URL")?;
+    let pool = PgPoolOptions::new().connect(&dsn).await?;
+
+    let persistence = PostgresPersistence::new_with_options(
+        pool,
+        PostgresOptions {
+            schema: Some(schema.into()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // Clear what previous runs left behind, such that next returns the events of this run
+    for state in [
+        OutboxState::Inserted,
+        OutboxState::Handled,
+        OutboxState::Failed,
+    ] {
+        persistence.purge(state, Duration::from_secs(0)).await?;
+    }
+
+    Ok(persistence)
+}
+
+async fn insert(persistence: &PostgresPersistence) -> anyhow::Result<uuid::Uuid> {
+    persistence
+        .insert(
+            &EventInfo {
+                domain: "some-domain".into(),
+                entity_type: "some-entity-type".into(),
+                event_name: "some-event-name".into(),
+            },
+            b"some-strange-and-cruncy-content".to_vec(),
+        )
+        .await?;
+
+    let (event_id, _) = persistence.next().await?.unwrap();
+
+    Ok(event_id.parse()?)
+}
+
+#[tokio::test]
+async fn test_admin_requeue() -> anyhow::Result<()> {
+    let persistence = persistence("crunch_admin_requeue").await?;
+
+    let event_id = insert(&persistence).await?;
+    persistence.update_published(&event_id.to_string()).await?;
+
+    let event = persistence.show(event_id).await?.unwrap();
+    assert_eq!(OutboxState::Handled.as_str(), event.state);
+    assert!(persistence
+        .list(OutboxState::Handled, i64::MAX)
+        .await?
+        .iter()
+        .any(|e| e.id == event_id));
+
+    assert!(persistence.requeue(event_id).await?);
+    let event = persistence.show(event_id).await?.unwrap();
+    assert_eq!(OutboxState::Inserted.as_str(), event.state);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_admin_purge() -> anyhow::Result<()> {
+    let persistence = persistence("crunch_admin_purge").await?;
+
+    let handled_id = insert(&persistence).await?;
+    persistence
+        .update_published(&handled_id.to_string())
+        .await?;
+    let inserted_id = insert(&persistence).await?;
+
+    assert_eq!(
+        1,
+        persistence
+            .purge(OutboxState::Handled, Duration::from_secs(0))
+            .await?
+    );
+    assert!(persistence.show(handled_id).await?.is_none());
+    assert!(persistence.show(inserted_id).await?.is_some());
+
+    Ok(())
+}
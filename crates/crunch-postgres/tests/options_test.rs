@@ -0,0 +1,83 @@
+use crunch_postgres::{PostgresOptions, PostgresPersistence};
+use crunch_traits::{EventInfo, Persistence};
+use sqlx::postgres::PgPoolOptions;
+
+#[test]
+fn test_migration_sql() -> anyhow::Result<()> {
+    let sql = PostgresPersistence::migration_sql(&PostgresOptions {
+        schema: Some("tenant_a".into()),
+        table: "events_outbox".into(),
+        run_migrations: false,
+    })?;
+
+    assert!(sql.starts_with(r#"CREATE SCHEMA IF NOT EXISTS "tenant_a";"#));
+    assert!(sql.contains(r#"CREATE TABLE IF NOT EXISTS "tenant_a"."events_outbox" ("#));
+
+    let sql = PostgresPersistence::migration_sql(&PostgresOptions::default())?;
+    assert!(sql.starts_with(r#"CREATE TABLE IF NOT EXISTS "outbox" ("#));
+
+    Ok(())
+}
+
+#[test]
+fn test_shipped_sql_matches_migration_sql() -> anyhow::Result<()> {
+    pretty_assertions::assert_eq!(
+        include_str!("../sql/outbox.sql"),
+        PostgresPersistence::migration_sql(&PostgresOptions::default())?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_migration_sql_rejects_invalid_identifiers() {
+    assert!(PostgresPersistence::migration_sql(&PostgresOptions {
+        table: r#"outbox"; DROP TABLE users; --"#.into(),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_tenants_share_pool() -> anyhow::Result<()> {
+    let dsn = std::env::var("DATABASE_URL")?;
+    let pool = PgPoolOptions::new().connect(&dsn).await?;
+
+    let tenant_a = PostgresPersistence::new_with_options(
+        pool.clone(),
+        PostgresOptions {
+            schema: Some("crunch_tenant_a".into()),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let tenant_b = PostgresPersistence::new_with_options(
+        pool,
+        PostgresOptions {
+            schema: Some("crunch_tenant_b".into()),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // Drain what previous runs left behind
+    while let Some((event_id, _)) = tenant_b.next().await? {
+        tenant_b.update_published(&event_id).await?;
+    }
+
+    tenant_a
+        .insert(
+            &EventInfo {
+                domain: "some-domain".into(),
+                entity_type: "some-entity-type".into(),
+                event_name: "some-event-name".into(),
+            },
+            b"some-strange-and-cruncy-content".to_vec(),
+        )
+        .await?;
+
+    assert!(tenant_a.next().await?.is_some());
+    assert!(tenant_b.next().await?.is_none());
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use crunch_postgres::{PostgresTransport, PostgresTransportOptions};
+use crunch_traits::{
+    testing::{broker_url, event_info, next_event, publish_subscribe},
+    Transport,
+};
+use sqlx::postgres::PgPoolOptions;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn transport(dsn: &str, consumer: &str) -> anyhow::Result<PostgresTransport> {
+    let pool = PgPoolOptions::new().connect(dsn).await?;
+
+    let mut options = PostgresTransportOptions::new(consumer);
+    options.poll_interval = Duration::from_millis(100);
+
+    PostgresTransport::new(pool, options).await
+}
+
+#[tokio::test]
+async fn test_transport_publish_subscribe() -> anyhow::Result<()> {
+    let Some(dsn) = broker_url("DATABASE_URL") else {
+        return Ok(());
+    };
+
+    publish_subscribe(&transport(&dsn, "test-publish-subscribe").await?, TIMEOUT).await
+}
+
+#[tokio::test]
+async fn test_transport_resumes_from_consumer_offset() -> anyhow::Result<()> {
+    let Some(dsn) = broker_url("DATABASE_URL") else {
+        return Ok(());
+    };
+    let transport = transport(&dsn, "test-resumes").await?;
+    let event_info = event_info();
+
+    let mut stream = transport.subscriber(&event_info).await?.unwrap();
+    transport.publish(&event_info, b"first".to_vec()).await?;
+    transport.publish(&event_info, b"second".to_vec()).await?;
+
+    assert_eq!(b"first".to_vec(), next_event(&mut stream, TIMEOUT).await?);
+    drop(stream);
+
+    // The first event was never acknowledged by asking for the next, so it is delivered again
+    let mut stream = transport.subscriber(&event_info).await?.unwrap();
+    assert_eq!(b"first".to_vec(), next_event(&mut stream, TIMEOUT).await?);
+    assert_eq!(b"second".to_vec(), next_event(&mut stream, TIMEOUT).await?);
+
+    // While another consumer only gets what is published after it subscribed
+    let other = self::transport(&dsn, "test-resumes-other").await?;
+    let mut other_stream = other.subscriber(&event_info).await?.unwrap();
+    transport.publish(&event_info, b"third".to_vec()).await?;
+    assert_eq!(
+        b"third".to_vec(),
+        next_event(&mut other_stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transport_instances_take_turns() -> anyhow::Result<()> {
+    let Some(dsn) = broker_url("DATABASE_URL") else {
+        return Ok(());
+    };
+    let first = transport(&dsn, "test-take-turns").await?;
+    let second = transport(&dsn, "test-take-turns").await?;
+    let event_info = event_info();
+
+    let mut first_stream = first.subscriber(&event_info).await?.unwrap();
+    let mut second_stream = second.subscriber(&event_info).await?.unwrap();
+    first.publish(&event_info, b"first".to_vec()).await?;
+    first.publish(&event_info, b"second".to_vec()).await?;
+
+    assert_eq!(
+        b"first".to_vec(),
+        next_event(&mut first_stream, TIMEOUT).await?
+    );
+    assert_eq!(
+        b"second".to_vec(),
+        next_event(&mut first_stream, TIMEOUT).await?
+    );
+    // The first instance holds the topic
+    assert!(next_event(&mut second_stream, Duration::from_millis(500))
+        .await
+        .is_err());
+
+    // Once it is gone the second takes over, from the event the first didn't acknowledge
+    drop(first_stream);
+    assert_eq!(
+        b"second".to_vec(),
+        next_event(&mut second_stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
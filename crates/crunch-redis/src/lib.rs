@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::TransportError, EventInfo, Transport};
+use futures::Stream;
+use redis::{
+    aio::MultiplexedConnection,
+    streams::{StreamId, StreamRangeReply, StreamReadOptions, StreamReadReply},
+    AsyncCommands, Client,
+};
+
+const CONTENT_FIELD: &str = "content";
+
+#[derive(Debug, Clone)]
+pub struct RedisTransportOptions {
+    /// Consumer group, each group receives every event once. Instances of a service should share it
+    pub group: String,
+    /// Name of this instance within the group, defaults to a random one
+    pub consumer: String,
+    /// How long a read waits for new events, before looking for entries to reclaim
+    pub block: Duration,
+    /// Entries delivered to a consumer this long ago without being acknowledged are reclaimed, as their consumer
+    /// probably crashed
+    pub claim_idle: Duration,
+    pub batch_size: usize,
+}
+
+impl RedisTransportOptions {
+    pub fn new(group: impl Into<String>) -> Self {
+        Self {
+            group: group.into(),
+            consumer: uuid::Uuid::new_v4().to_string(),
+            block: Duration::from_secs(5),
+            claim_idle: Duration::from_secs(60),
+            batch_size: 100,
+        }
+    }
+}
+
+/// A transport on redis streams, events are added to a stream per event with XADD and read through consumer groups.
+///
+/// An event is acknowledged once the subscription asks for the next one, such that events being handled when a
+/// consumer crashes are reclaimed by another
+#[derive(Clone)]
+pub struct RedisTransport {
+    client: Client,
+    conn: MultiplexedConnection,
+    options: RedisTransportOptions,
+}
+
+impl RedisTransport {
+    pub async fn new(url: &str, options: RedisTransportOptions) -> Result<Self, TransportError> {
+        let client = Client::open(url)
+            .map_err(|e| anyhow::anyhow!("invalid redis url: {}: {}", url, e))
+            .map_err(TransportError::Err)?;
+        let conn = client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to connect to redis: {}", e))
+            .map_err(TransportError::Err)?;
+
+        Ok(Self {
+            client,
+            conn,
+            options,
+        })
+    }
+}
+
+struct Subscription {
+    // Blocking reads would hold up everyone sharing a connection, so each subscription has its own
+    conn: MultiplexedConnection,
+    stream: String,
+    options: RedisTransportOptions,
+    /// Id of the entry handed out last, it is acknowledged when the next one is asked for
+    unacked: Option<String>,
+    buffer: VecDeque<StreamId>,
+    last_claim: Option<Instant>,
+}
+
+impl Subscription {
+    async fn next(&mut self) -> anyhow::Result<Vec<u8>> {
+        if let Some(id) = &self.unacked {
+            let _: () = self
+                .conn
+                .xack(&self.stream, &self.options.group, &[id])
+                .await?;
+            self.unacked = None;
+        }
+
+        loop {
+            if let Some(entry) = self.buffer.pop_front() {
+                match entry.get::<Vec<u8>>(CONTENT_FIELD) {
+                    Some(content) => {
+                        self.unacked = Some(entry.id);
+                        return Ok(content);
+                    }
+                    None => {
+                        tracing::warn!("skipping entry: {} without content", entry.id);
+                        let _: () = self
+                            .conn
+                            .xack(&self.stream, &self.options.group, &[&entry.id])
+                            .await?;
+                        continue;
+                    }
+                }
+            }
+
+            let claim_due = self
+                .last_claim
+                .is_none_or(|c| c.elapsed() >= self.options.claim_idle);
+            if claim_due {
+                self.last_claim = Some(Instant::now());
+                let claimed = self.reclaim().await?;
+                self.buffer.extend(claimed);
+                if !self.buffer.is_empty() {
+                    continue;
+                }
+            }
+
+            let reply: Option<StreamReadReply> = self
+                .conn
+                .xread_options(
+                    &[&self.stream],
+                    &[">"],
+                    &StreamReadOptions::default()
+                        .group(&self.options.group, &self.options.consumer)
+                        .count(self.options.batch_size)
+                        .block(self.options.block.as_millis() as usize),
+                )
+                .await?;
+            for key in reply.into_iter().flat_map(|r| r.keys) {
+                self.buffer.extend(key.ids);
+            }
+        }
+    }
+
+    /// Claims the entries left pending by crashed consumers of the group, including this one before a restart
+    async fn reclaim(&mut self) -> anyhow::Result<Vec<StreamId>> {
+        let reply: redis::Value = redis::cmd("XAUTOCLAIM")
+            .arg(&self.stream)
+            .arg(&self.options.group)
+            .arg(&self.options.consumer)
+            .arg(self.options.claim_idle.as_millis() as u64)
+            .arg("0-0")
+            .arg("COUNT")
+            .arg(self.options.batch_size)
+            .query_async(&mut self.conn)
+            .await?;
+
+        // The reply is the cursor to continue from, the claimed entries and on redis 7 the ids of deleted entries
+        let claimed = match reply {
+            redis::Value::Array(items) if items.len() >= 2 => {
+                redis::from_redis_value::<StreamRangeReply>(&items[1])?.ids
+            }
+            reply => anyhow::bail!("unexpected reply to XAUTOCLAIM: {:?}", reply),
+        };
+        if !claimed.is_empty() {
+            tracing::debug!(
+                "reclaimed {} pending entries of: {}",
+                claimed.len(),
+                self.stream
+            );
+        }
+
+        Ok(claimed)
+    }
+}
+
+#[async_trait]
+impl Transport for RedisTransport {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        let mut conn = self.conn.clone();
+        let _: String = conn
+            .xadd(
+                event_info.transport_name(),
+                "*",
+                &[(CONTENT_FIELD, content.as_slice())],
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+            .map_err(TransportError::Err)?;
+
+        Ok(())
+    }
+
+    async fn subscriber(
+        &self,
+        event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        let stream = event_info.transport_name();
+
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to connect to redis: {}", e))
+            .map_err(TransportError::Err)?;
+        // New groups start at the end of the stream, like a subscription to any other transport
+        let created: redis::RedisResult<()> = conn
+            .xgroup_create_mkstream(&stream, &self.options.group, "$")
+            .await;
+        match created {
+            Ok(()) => {}
+            Err(e) if e.code() == Some("BUSYGROUP") => {}
+            Err(e) => {
+                return Err(TransportError::Err(anyhow::anyhow!(
+                    "failed to create consumer group: {}",
+                    e
+                )))
+            }
+        }
+
+        let subscription = Subscription {
+            conn,
+            stream,
+            options: self.options.clone(),
+            unacked: None,
+            buffer: VecDeque::new(),
+            last_claim: None,
+        };
+
+        let stream = futures::stream::unfold(subscription, |mut subscription| async move {
+            loop {
+                match subscription.next().await {
+                    Ok(content) => {
+                        tracing::trace!("got event from redis");
+                        return Some((content, subscription));
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to receive event from redis: {e}");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Some(Box::pin(stream)))
+    }
+}
+
+trait EventInfoExt {
+    fn transport_name(&self) -> String;
+}
+
+impl EventInfoExt for EventInfo {
+    fn transport_name(&self) -> String {
+        format!(
+            "crunch.{}.{}.{}",
+            self.domain, self.entity_type, self.event_name
+        )
+    }
+}
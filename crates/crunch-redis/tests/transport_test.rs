@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use crunch_redis::{RedisTransport, RedisTransportOptions};
+use crunch_traits::{
+    testing::{broker_url, event_info, next_event, publish_subscribe},
+    Transport,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn options(group: &str) -> RedisTransportOptions {
+    let mut options = RedisTransportOptions::new(group);
+    options.block = Duration::from_millis(100);
+
+    options
+}
+
+#[tokio::test]
+async fn test_transport_publish_subscribe() -> anyhow::Result<()> {
+    let Some(url) = broker_url("REDIS_URL") else {
+        return Ok(());
+    };
+
+    publish_subscribe(
+        &RedisTransport::new(&url, options("test-publish-subscribe")).await?,
+        TIMEOUT,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_transport_reclaims_from_crashed_consumer() -> anyhow::Result<()> {
+    let Some(url) = broker_url("REDIS_URL") else {
+        return Ok(());
+    };
+    let event_info = event_info();
+    let mut crashed_options = options("test-reclaims");
+    crashed_options.claim_idle = Duration::from_millis(200);
+    let crashed = RedisTransport::new(&url, crashed_options.clone()).await?;
+
+    let mut stream = crashed.subscriber(&event_info).await?.unwrap();
+    crashed.publish(&event_info, b"first".to_vec()).await?;
+
+    assert_eq!(b"first".to_vec(), next_event(&mut stream, TIMEOUT).await?);
+    // The first event was never acknowledged by asking for the next
+    drop(stream);
+
+    let mut other_options = crashed_options;
+    other_options.consumer = uuid::Uuid::new_v4().to_string();
+    let other = RedisTransport::new(&url, other_options).await?;
+    let mut other_stream = other.subscriber(&event_info).await?.unwrap();
+    assert_eq!(
+        b"first".to_vec(),
+        next_event(&mut other_stream, TIMEOUT).await?
+    );
+
+    // Once acknowledged it stays with the group
+    other.publish(&event_info, b"second".to_vec()).await?;
+    assert_eq!(
+        b"second".to_vec(),
+        next_event(&mut other_stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transport_groups_each_receive_events() -> anyhow::Result<()> {
+    let Some(url) = broker_url("REDIS_URL") else {
+        return Ok(());
+    };
+    let event_info = event_info();
+    let first = RedisTransport::new(&url, options("test-groups-first")).await?;
+    let second = RedisTransport::new(&url, options("test-groups-second")).await?;
+
+    let mut first_stream = first.subscriber(&event_info).await?.unwrap();
+    let mut second_stream = second.subscriber(&event_info).await?.unwrap();
+    first.publish(&event_info, b"event".to_vec()).await?;
+
+    assert_eq!(
+        b"event".to_vec(),
+        next_event(&mut first_stream, TIMEOUT).await?
+    );
+    assert_eq!(
+        b"event".to_vec(),
+        next_event(&mut second_stream, TIMEOUT).await?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::{component, copy_protos, SchemaRef, SchemaRegistry};
+
+/// A registry kept in a plain directory, i.e. a shared contracts folder in a monorepo
+pub struct DirectoryRegistry {
+    root: PathBuf,
+}
+
+impl DirectoryRegistry {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl SchemaRegistry for DirectoryRegistry {
+    async fn publish(&self, schema: &SchemaRef, schema_paths: &[PathBuf]) -> anyhow::Result<()> {
+        let destination = self.root.join(schema.path()?);
+        if destination.exists() {
+            anyhow::bail!(
+                "schema: {} is already published, bump the version to publish changes",
+                schema
+            );
+        }
+
+        let mut copied = 0;
+        for schema_path in schema_paths {
+            copied += copy_protos(schema_path, &destination).await?;
+        }
+        if copied == 0 {
+            // Don't leave an empty version behind which would block a later publish
+            if destination.exists() {
+                tokio::fs::remove_dir_all(&destination).await?;
+            }
+            anyhow::bail!("no protobuf files found to publish for: {}", schema);
+        }
+
+        tracing::debug!("published {} schemas for: {}", copied, schema);
+
+        Ok(())
+    }
+
+    async fn fetch(&self, schema: &SchemaRef, destination: &Path) -> anyhow::Result<()> {
+        let source = self.root.join(schema.path()?);
+        if !source.exists() {
+            anyhow::bail!("schema: {} is not published to the registry", schema);
+        }
+
+        // Subscriptions read from a directory registry in place
+        if destination.exists() && source.canonicalize()? == destination.canonicalize()? {
+            return Ok(());
+        }
+
+        if destination.exists() {
+            tokio::fs::remove_dir_all(destination).await?;
+        }
+        copy_protos(&source, destination).await?;
+
+        Ok(())
+    }
+
+    async fn versions(&self, domain: &str, service: &str) -> anyhow::Result<Vec<String>> {
+        let service_path = self
+            .root
+            .join(component("domain", domain)?)
+            .join(component("service", service)?);
+        if !service_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut versions = Vec::new();
+        let mut entries = tokio::fs::read_dir(service_path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                versions.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+
+        Ok(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_can_publish_and_fetch() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let schemas = dir.path().join("schemas");
+        tokio::fs::create_dir_all(schemas.join("nested")).await?;
+        tokio::fs::write(schemas.join("my_event.proto"), "syntax = \"proto3\";").await?;
+        tokio::fs::write(schemas.join("nested/other.proto"), "syntax = \"proto3\";").await?;
+        tokio::fs::write(schemas.join("README.md"), "not a schema").await?;
+
+        let registry = DirectoryRegistry::new(dir.path().join("registry"));
+        let schema = SchemaRef::new("my-domain", "my-service", "1.0.0");
+        registry.publish(&schema, &[schemas]).await?;
+
+        let destination = dir.path().join("fetched");
+        registry.fetch(&schema, &destination).await?;
+
+        let mut fetched = walkdir::WalkDir::new(&destination)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                e.path()
+                    .strip_prefix(&destination)
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        fetched.sort();
+
+        pretty_assertions::assert_eq!(fetched, vec!["my_event.proto", "nested/other.proto"]);
+        pretty_assertions::assert_eq!(
+            registry.versions("my-domain", "my-service").await?,
+            vec!["1.0.0"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cannot_overwrite_published_version() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let schemas = dir.path().join("schemas");
+        tokio::fs::create_dir_all(&schemas).await?;
+        tokio::fs::write(schemas.join("my_event.proto"), "syntax = \"proto3\";").await?;
+
+        let registry = DirectoryRegistry::new(dir.path().join("registry"));
+        let schema = SchemaRef::new("my-domain", "my-service", "1.0.0");
+        registry
+            .publish(&schema, std::slice::from_ref(&schemas))
+            .await?;
+
+        assert!(registry.publish(&schema, &[schemas]).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rejects_schema_outside_of_root() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let destination = dir.path().join("fetched");
+        tokio::fs::create_dir_all(&destination).await?;
+        tokio::fs::create_dir_all(dir.path().join("x")).await?;
+
+        let registry = DirectoryRegistry::new(dir.path().join("registry"));
+        for schema in [
+            SchemaRef::new("my-domain", "my-service", "../../x"),
+            SchemaRef::new("my-domain", "my-service", ".."),
+            SchemaRef::new("my-domain", "my-service", "."),
+            SchemaRef::new("my-domain", "my-service", ""),
+            SchemaRef::new("my-domain", "my-service", "1.0.0/.."),
+            SchemaRef::new("/tmp", "my-service", "1.0.0"),
+        ] {
+            assert!(schema.path().is_err(), "{:?}", schema);
+            assert!(registry.fetch(&schema, &destination).await.is_err());
+        }
+        assert!(registry.versions("..", "my-service").await.is_err());
+
+        // The destination is left alone
+        assert!(destination.exists());
+
+        Ok(())
+    }
+}
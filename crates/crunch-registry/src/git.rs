@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::{DirectoryRegistry, SchemaRef, SchemaRegistry};
+
+/// A registry kept in a git repository, every operation works on a fresh shallow clone using the `git` cli,
+/// such that the users existing credentials and configuration apply
+pub struct GitRegistry {
+    url: String,
+    branch: Option<String>,
+}
+
+impl GitRegistry {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            branch: None,
+        }
+    }
+
+    pub fn with_branch(&mut self, branch: impl Into<String>) -> &mut Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    async fn checkout(&self) -> anyhow::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(branch) = &self.branch {
+            args.extend(["--branch", branch]);
+        }
+        let checkout_path = dir.path().display().to_string();
+        args.extend([self.url.as_str(), checkout_path.as_str()]);
+
+        tracing::debug!("cloning registry: {}", self.url);
+        git(None, &args).await?;
+
+        Ok(dir)
+    }
+}
+
+#[async_trait]
+impl SchemaRegistry for GitRegistry {
+    async fn publish(&self, schema: &SchemaRef, schema_paths: &[PathBuf]) -> anyhow::Result<()> {
+        let checkout = self.checkout().await?;
+
+        DirectoryRegistry::new(checkout.path())
+            .publish(schema, schema_paths)
+            .await?;
+
+        let schema_path = schema.path()?.display().to_string();
+        git(Some(checkout.path()), &["add", &schema_path]).await?;
+        git(
+            Some(checkout.path()),
+            &["commit", "-m", &format!("publish {}", schema)],
+        )
+        .await?;
+        git(Some(checkout.path()), &["push", "origin", "HEAD"]).await?;
+
+        Ok(())
+    }
+
+    async fn fetch(&self, schema: &SchemaRef, destination: &Path) -> anyhow::Result<()> {
+        let checkout = self.checkout().await?;
+
+        DirectoryRegistry::new(checkout.path())
+            .fetch(schema, destination)
+            .await
+    }
+
+    async fn versions(&self, domain: &str, service: &str) -> anyhow::Result<Vec<String>> {
+        let checkout = self.checkout().await?;
+
+        DirectoryRegistry::new(checkout.path())
+            .versions(domain, service)
+            .await
+    }
+}
+
+async fn git(dir: Option<&Path>, args: &[&str]) -> anyhow::Result<()> {
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.args(args);
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+
+    tracing::trace!("running: git {}", args.join(" "));
+    let output = cmd.output().await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+mod directory;
+mod git;
+
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+
+pub use directory::DirectoryRegistry;
+pub use git::GitRegistry;
+
+/// Identifies a published set of schemas, stored at `domain/service/version` in a registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaRef {
+    pub domain: String,
+    pub service: String,
+    pub version: String,
+}
+
+impl SchemaRef {
+    pub fn new(
+        domain: impl Into<String>,
+        service: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        Self {
+            domain: domain.into(),
+            service: service.into(),
+            version: version.into(),
+        }
+    }
+
+    /// Path of the schemas relative to the registry root. Each part has to be a single plain path component, such that
+    /// a schema can't point outside of the registry
+    pub fn path(&self) -> anyhow::Result<PathBuf> {
+        Ok(PathBuf::from(component("domain", &self.domain)?)
+            .join(component("service", &self.service)?)
+            .join(component("version", &self.version)?))
+    }
+}
+
+// Rejects empty, `.`, `..`, absolute and multi component values
+fn component<'a>(name: &str, value: &'a str) -> anyhow::Result<&'a str> {
+    let mut components = Path::new(value).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !value.contains(['/', '\\']) => Ok(value),
+        _ => anyhow::bail!(
+            "invalid schema {}: {:?}, it has to be a plain name without path separators",
+            name,
+            value
+        ),
+    }
+}
+
+impl std::fmt::Display for SchemaRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}@{}", self.domain, self.service, self.version)
+    }
+}
+
+#[async_trait]
+pub trait SchemaRegistry {
+    /// Publishes the protobuf files found in `schema_paths`, a published version cannot be overwritten
+    async fn publish(&self, schema: &SchemaRef, schema_paths: &[PathBuf]) -> anyhow::Result<()>;
+    /// Places the protobuf files of a published version into `destination`
+    async fn fetch(&self, schema: &SchemaRef, destination: &Path) -> anyhow::Result<()>;
+    /// All published versions of a service, in no particular order
+    async fn versions(&self, domain: &str, service: &str) -> anyhow::Result<Vec<String>>;
+}
+
+pub type DynSchemaRegistry = Box<dyn SchemaRegistry + Send + Sync + 'static>;
+
+/// Creates the registry described by `[registry]`, relative paths are resolved from `crunch_dir`
+pub fn from_config(
+    registry: &crunch_file::Registry,
+    crunch_dir: &Path,
+) -> anyhow::Result<DynSchemaRegistry> {
+    match (&registry.path, &registry.git) {
+        (Some(path), None) => Ok(Box::new(DirectoryRegistry::new(crunch_dir.join(path)))),
+        (None, Some(git)) => {
+            let mut registry_git = GitRegistry::new(git);
+            if let Some(branch) = &registry.branch {
+                registry_git.with_branch(branch);
+            }
+            Ok(Box::new(registry_git))
+        }
+        (Some(_), Some(_)) => anyhow::bail!("[registry] can only have one of path or git"),
+        (None, None) => anyhow::bail!("[registry] requires either path or git"),
+    }
+}
+
+async fn copy_protos(source: &Path, destination: &Path) -> anyhow::Result<usize> {
+    let mut copied = 0;
+    for entry in walkdir::WalkDir::new(source) {
+        let entry = entry?;
+        if !entry.file_type().is_file()
+            || entry.path().extension().and_then(|e| e.to_str()) != Some("proto")
+        {
+            continue;
+        }
+
+        let rel_path = entry.path().strip_prefix(source)?;
+        let file_destination = destination.join(rel_path);
+        if let Some(dir) = file_destination.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        tracing::trace!(
+            "copying schema: {} to {}",
+            entry.path().display(),
+            file_destination.display()
+        );
+        tokio::fs::copy(entry.path(), &file_destination).await?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
@@ -0,0 +1,261 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::PersistenceError, EventInfo};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    Executor, Pool, Sqlite, Transaction,
+};
+use uuid::Uuid;
+
+pub struct SqliteTx {}
+
+impl crunch_traits::Tx for SqliteTx {}
+
+pub struct SqliteOptions {
+    pub table: String,
+    /// Creates the table if it is missing. Turn it off to apply [`SqlitePersistence::migration_sql`] through your own
+    /// migration tool instead
+    pub run_migrations: bool,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        Self {
+            table: "outbox".into(),
+            run_migrations: true,
+        }
+    }
+}
+
+// Identifiers can't be bound as parameters, so only plain ones are allowed into the queries
+fn quote_identifier(identifier: &str) -> anyhow::Result<String> {
+    let valid = identifier
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        anyhow::bail!(
+            "invalid sqlite identifier: {}, it can only contain letters, numbers and _",
+            identifier
+        );
+    }
+
+    Ok(format!("\"{}\"", identifier))
+}
+
+pub struct SqlitePersistence {
+    pool: Pool<Sqlite>,
+    table: String,
+    claim_timeout: Duration,
+}
+
+impl SqlitePersistence {
+    /// Opens the database at `path`, creating it if it doesn't exist, and creates the outbox table
+    pub async fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(Duration::from_secs(5));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+
+        Self::new_from_pool(pool).await
+    }
+
+    /// Uses the pool of the application, such that events can be inserted in its transactions. Creates the outbox
+    /// table
+    pub async fn new_from_pool(pool: Pool<Sqlite>) -> anyhow::Result<Self> {
+        Self::new_with_options(pool, SqliteOptions::default()).await
+    }
+
+    pub async fn new_with_options(
+        pool: Pool<Sqlite>,
+        options: SqliteOptions,
+    ) -> anyhow::Result<Self> {
+        if options.run_migrations {
+            pool.execute(Self::migration_sql(&options)?.as_str())
+                .await?;
+        }
+
+        Ok(Self {
+            pool,
+            table: quote_identifier(&options.table)?,
+            claim_timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// The sql creating the outbox for `options`, safe to run more than once
+    pub fn migration_sql(options: &SqliteOptions) -> anyhow::Result<String> {
+        Ok(format!(
+            r#"CREATE TABLE IF NOT EXISTS {table} (
+    id TEXT NOT NULL PRIMARY KEY,
+    domain TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    event_name TEXT NOT NULL,
+    content BLOB NOT NULL,
+    -- unix milliseconds
+    inserted_time INTEGER NOT NULL,
+    state TEXT NOT NULL,
+    -- a claimed event is handed out again once its claim expires, in case the relay died while publishing it
+    claimed_until INTEGER
+);
+CREATE INDEX IF NOT EXISTS {index} ON {table} (state, inserted_time);
+"#,
+            table = quote_identifier(&options.table)?,
+            index = quote_identifier(&format!("{}_state_inserted_time", options.table))?,
+        ))
+    }
+
+    /// How long a claimed event may take to be published, before it is handed out again. Defaults to 30 seconds
+    pub fn with_claim_timeout(mut self, claim_timeout: Duration) -> Self {
+        self.claim_timeout = claim_timeout;
+        self
+    }
+
+    /// Inserts the event as part of `tx`, such that it is only published if the transaction commits
+    pub async fn insert_tx(
+        &self,
+        tx: &mut Transaction<'_, Sqlite>,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(&format!(
+            r#"
+INSERT INTO {} (id, domain, entity_type, event_name, content, inserted_time, state)
+VALUES ($1, $2, $3, $4, $5, $6, 'inserted');
+"#,
+            self.table
+        ))
+        .bind(Uuid::new_v4().to_string())
+        .bind(&event_info.domain)
+        .bind(&event_info.entity_type)
+        .bind(&event_info.event_name)
+        .bind(content)
+        .bind(now_millis())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time to be after the unix epoch")
+        .as_millis() as i64
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimResp {
+    id: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct OutboxEvent {
+    domain: String,
+    entity_type: String,
+    event_name: String,
+    content: Vec<u8>,
+}
+
+#[async_trait]
+impl crunch_traits::Persistence for SqlitePersistence {
+    async fn insert(&self, event_info: &EventInfo, content: Vec<u8>) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        self.insert_tx(&mut tx, event_info, content).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Claiming is a single statement, and sqlite serializes writers, so an event is only handed out once per claim
+    async fn next(&self) -> Result<Option<(String, crunch_traits::DynTx)>, PersistenceError> {
+        let now = now_millis();
+        let resp = sqlx::query_as::<_, ClaimResp>(&format!(
+            r#"
+UPDATE {table}
+SET state = 'claimed', claimed_until = $1
+WHERE id = (
+    SELECT id
+    FROM {table}
+    WHERE state = 'inserted' OR (state = 'claimed' AND claimed_until < $2)
+    ORDER BY inserted_time ASC
+    LIMIT 1
+)
+RETURNING id;
+"#,
+            table = self.table
+        ))
+        .bind(now + self.claim_timeout.as_millis() as i64)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map_err(PersistenceError::AnyErr)?;
+
+        Ok(resp.map(|ClaimResp { id }| (id, Box::new(SqliteTx {}) as crunch_traits::DynTx)))
+    }
+
+    async fn get(&self, event_id: &str) -> Result<Option<(EventInfo, Vec<u8>)>, PersistenceError> {
+        let event = sqlx::query_as::<_, OutboxEvent>(&format!(
+            r#"
+SELECT domain, entity_type, event_name, content
+FROM {}
+WHERE id = $1 AND state != 'handled';
+"#,
+            self.table
+        ))
+        .bind(event_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map_err(PersistenceError::GetErr)?;
+
+        Ok(event.map(|event| {
+            (
+                EventInfo {
+                    domain: event.domain,
+                    entity_type: event.entity_type,
+                    event_name: event.event_name,
+                },
+                event.content,
+            )
+        }))
+    }
+
+    async fn update_published(&self, event_id: &str) -> Result<(), PersistenceError> {
+        let result = sqlx::query(&format!(
+            r#"
+UPDATE {}
+SET state = 'handled', claimed_until = NULL
+WHERE id = $1;
+"#,
+            self.table
+        ))
+        .bind(event_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map_err(PersistenceError::UpdatePublished)?;
+
+        if result.rows_affected() == 0 {
+            return Err(PersistenceError::UpdatePublished(anyhow::anyhow!(
+                "event was not found on id: {}",
+                event_id
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use crunch_sqlite::{SqliteOptions, SqlitePersistence};
+use crunch_traits::{EventInfo, Persistence};
+
+fn event_info() -> EventInfo {
+    EventInfo {
+        domain: "some-domain".into(),
+        entity_type: "some-entity-type".into(),
+        event_name: "some-event-name".into(),
+    }
+}
+
+#[tokio::test]
+async fn test_persistence_publish_flow() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let persistence = SqlitePersistence::new(dir.path().join("outbox.db")).await?;
+
+    persistence
+        .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+        .await?;
+
+    let (event_id, _) = persistence.next().await?.unwrap();
+    // A claimed event isn't handed out twice
+    assert!(persistence.next().await?.is_none());
+
+    let (info, content) = persistence.get(&event_id).await?.unwrap();
+    assert_eq!("some-event-name", info.event_name);
+    assert_eq!(b"some-strange-and-cruncy-content".to_vec(), content);
+
+    persistence.update_published(&event_id).await?;
+    assert!(persistence.get(&event_id).await?.is_none());
+    assert!(persistence.next().await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_reclaims_expired_claims() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let persistence = SqlitePersistence::new(dir.path().join("outbox.db"))
+        .await?
+        .with_claim_timeout(Duration::from_millis(0));
+
+    persistence
+        .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+        .await?;
+
+    let (event_id, _) = persistence.next().await?.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let (reclaimed_id, _) = persistence.next().await?.unwrap();
+    assert_eq!(event_id, reclaimed_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_insert_tx() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let persistence = SqlitePersistence::new(dir.path().join("outbox.db")).await?;
+    let pool = sqlx::SqlitePool::connect(&format!(
+        "sqlite://{}",
+        dir.path().join("outbox.db").display()
+    ))
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    persistence
+        .insert_tx(&mut tx, &event_info(), b"rolled-back".to_vec())
+        .await?;
+    tx.rollback().await?;
+    assert!(persistence.next().await?.is_none());
+
+    let mut tx = pool.begin().await?;
+    persistence
+        .insert_tx(&mut tx, &event_info(), b"committed".to_vec())
+        .await?;
+    tx.commit().await?;
+    assert!(persistence.next().await?.is_some());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_survives_reopen() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("outbox.db");
+
+    SqlitePersistence::new(&path)
+        .await?
+        .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+        .await?;
+
+    let persistence = SqlitePersistence::new(&path).await?;
+    assert!(persistence.next().await?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_migration_sql() -> anyhow::Result<()> {
+    let sql = SqlitePersistence::migration_sql(&SqliteOptions::default())?;
+    assert!(sql.starts_with(r#"CREATE TABLE IF NOT EXISTS "outbox" ("#));
+
+    assert!(SqlitePersistence::migration_sql(&SqliteOptions {
+        table: r#"outbox"; DROP TABLE users; --"#.into(),
+        ..Default::default()
+    })
+    .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_persistence_without_migrations() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pool = sqlx::SqlitePool::connect(&format!(
+        "sqlite://{}?mode=rwc",
+        dir.path().join("app.db").display()
+    ))
+    .await?;
+    let options = SqliteOptions {
+        table: "events_outbox".into(),
+        run_migrations: false,
+    };
+
+    // The application applies the sql through its own migrations
+    sqlx::raw_sql(&SqlitePersistence::migration_sql(&options)?)
+        .execute(&pool)
+        .await?;
+    let persistence = SqlitePersistence::new_with_options(pool.clone(), options).await?;
+
+    persistence
+        .insert(&event_info(), b"some-strange-and-cruncy-content".to_vec())
+        .await?;
+    assert!(persistence.next().await?.is_some());
+
+    let migrations = sqlx::query("SELECT name FROM sqlite_master WHERE name = '_sqlx_migrations'")
+        .fetch_optional(&pool)
+        .await?;
+    assert!(migrations.is_none());
+
+    Ok(())
+}
@@ -25,7 +25,7 @@ pub trait Deserializer {
         Self: Sized;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EventInfo {
     pub domain: String,
     pub entity_type: String,
@@ -49,6 +49,14 @@ pub trait Event: Serializer + Deserializer {
     }
 }
 
+/// The key of an event, events with the same key keep their order on transports which only order events within a
+/// partition, such as kafka. Usually the id of the entity the event belongs to
+pub trait PartitionKey: Event {
+    fn partition_key(&self) -> String;
+}
+
 pub mod errors;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod transport;
 pub use transport::*;
@@ -0,0 +1,60 @@
+//! Helpers shared by the tests of the transports, enabled with the `testing` feature
+
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+
+use crate::{EventInfo, Transport};
+
+/// The url of the broker to test against, read from `var`. Returns None when it isn't set, such that tests can skip
+/// instead of failing on machines without the broker
+pub fn broker_url(var: &str) -> Option<String> {
+    match std::env::var(var) {
+        Ok(url) => Some(url),
+        Err(_) => {
+            eprintln!("skipping, {} is not set", var);
+            None
+        }
+    }
+}
+
+/// An event with a name of its own, such that tests sharing a broker don't see the events of one another
+pub fn event_info() -> EventInfo {
+    EventInfo {
+        domain: "some-domain".into(),
+        entity_type: "some-entity-type".into(),
+        event_name: uuid::Uuid::new_v4().to_string(),
+    }
+}
+
+/// The next event of `stream`, failing when none arrives within `timeout`
+pub async fn next_event<S>(stream: &mut S, timeout: Duration) -> anyhow::Result<Vec<u8>>
+where
+    S: Stream<Item = Vec<u8>> + Unpin,
+{
+    tokio::time::timeout(timeout, stream.next())
+        .await
+        .map_err(|_| anyhow::anyhow!("no event within {:?}", timeout))?
+        .ok_or_else(|| anyhow::anyhow!("stream ended"))
+}
+
+/// Asserts that events published after subscribing are received in order
+pub async fn publish_subscribe<T>(transport: &T, timeout: Duration) -> anyhow::Result<()>
+where
+    T: Transport,
+    T::Stream: Unpin,
+{
+    let event_info = event_info();
+
+    let mut stream = transport
+        .subscriber(&event_info)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("transport has no subscriber"))?;
+    transport.publish(&event_info, b"first".to_vec()).await?;
+    transport.publish(&event_info, b"second".to_vec()).await?;
+
+    assert_eq!(b"first".to_vec(), next_event(&mut stream, timeout).await?);
+    assert_eq!(b"second".to_vec(), next_event(&mut stream, timeout).await?);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use crunch_traits::EventInfo;
+use uuid::Uuid;
+
+/// State of the delivery of an event to a single url
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Being delivered, or waiting for the next attempt
+    Pending,
+    Delivered,
+    /// Every attempt failed
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    /// Sent as the `crunch-webhook-id` header
+    pub id: Uuid,
+    pub url: String,
+    pub event_info: EventInfo,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub updated_time: chrono::DateTime<chrono::Utc>,
+}
+
+/// Keeps track of webhook deliveries, such that failed deliveries can be found and looked into
+#[async_trait]
+pub trait DeliveryStore {
+    /// Inserts the delivery, or updates it if it exists
+    async fn record(&self, delivery: &Delivery) -> anyhow::Result<()>;
+    /// Deliveries in `status`, oldest first
+    async fn list(&self, status: DeliveryStatus, limit: i64) -> anyhow::Result<Vec<Delivery>>;
+}
+
+pub type DynDeliveryStore = Arc<dyn DeliveryStore + Send + Sync + 'static>;
+
+/// Keeps deliveries for the lifetime of the process
+#[derive(Clone, Default)]
+pub struct InMemoryDeliveryStore {
+    deliveries: Arc<Mutex<HashMap<Uuid, Delivery>>>,
+}
+
+#[async_trait]
+impl DeliveryStore for InMemoryDeliveryStore {
+    async fn record(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        self.deliveries
+            .lock()
+            .unwrap()
+            .insert(delivery.id, delivery.clone());
+
+        Ok(())
+    }
+
+    async fn list(&self, status: DeliveryStatus, limit: i64) -> anyhow::Result<Vec<Delivery>> {
+        let mut deliveries: Vec<_> = self
+            .deliveries
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|d| d.status == status)
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|d| d.updated_time);
+        deliveries.truncate(limit.max(0) as usize);
+
+        Ok(deliveries)
+    }
+}
@@ -0,0 +1,14 @@
+mod deliveries;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod receiver;
+pub mod signature;
+mod transport;
+
+pub use deliveries::{
+    Delivery, DeliveryStatus, DeliveryStore, DynDeliveryStore, InMemoryDeliveryStore,
+};
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresDeliveryStore;
+pub use receiver::{WebhookError, WebhookReceiver};
+pub use transport::{WebhookTransport, WebhookTransportOptions};
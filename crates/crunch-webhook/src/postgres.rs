@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use crunch_traits::EventInfo;
+use sqlx::{Executor, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{Delivery, DeliveryStatus, DeliveryStore};
+
+const MIGRATION_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS crunch_webhook_deliveries (
+    id UUID PRIMARY KEY,
+    url VARCHAR NOT NULL,
+    domain VARCHAR NOT NULL,
+    entity_type VARCHAR NOT NULL,
+    event_name VARCHAR NOT NULL,
+    status VARCHAR NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error VARCHAR,
+    updated_time TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX IF NOT EXISTS crunch_webhook_deliveries_status ON crunch_webhook_deliveries (status, updated_time);
+"#;
+
+/// Keeps deliveries in the `crunch_webhook_deliveries` table, usually next to the outbox
+#[derive(Clone)]
+pub struct PostgresDeliveryStore {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresDeliveryStore {
+    /// Creates the table if it is missing
+    pub async fn new(pool: Pool<Postgres>) -> anyhow::Result<Self> {
+        pool.execute(MIGRATION_SQL).await?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DeliveryRow {
+    id: Uuid,
+    url: String,
+    domain: String,
+    entity_type: String,
+    event_name: String,
+    status: String,
+    attempts: i32,
+    last_error: Option<String>,
+    updated_time: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<DeliveryRow> for Delivery {
+    type Error = anyhow::Error;
+
+    fn try_from(value: DeliveryRow) -> Result<Self, Self::Error> {
+        let status = match value.status.as_str() {
+            "pending" => DeliveryStatus::Pending,
+            "delivered" => DeliveryStatus::Delivered,
+            "failed" => DeliveryStatus::Failed,
+            status => anyhow::bail!("unknown delivery status: {}", status),
+        };
+
+        Ok(Self {
+            id: value.id,
+            url: value.url,
+            event_info: EventInfo {
+                domain: value.domain,
+                entity_type: value.entity_type,
+                event_name: value.event_name,
+            },
+            status,
+            attempts: value.attempts as u32,
+            last_error: value.last_error,
+            updated_time: value.updated_time,
+        })
+    }
+}
+
+#[async_trait]
+impl DeliveryStore for PostgresDeliveryStore {
+    async fn record(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO crunch_webhook_deliveries
+    (id, url, domain, entity_type, event_name, status, attempts, last_error, updated_time)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+ON CONFLICT (id) DO UPDATE SET
+    status = EXCLUDED.status,
+    attempts = EXCLUDED.attempts,
+    last_error = EXCLUDED.last_error,
+    updated_time = EXCLUDED.updated_time;
+"#,
+        )
+        .bind(delivery.id)
+        .bind(&delivery.url)
+        .bind(&delivery.event_info.domain)
+        .bind(&delivery.event_info.entity_type)
+        .bind(&delivery.event_info.event_name)
+        .bind(delivery.status.as_str())
+        .bind(delivery.attempts as i32)
+        .bind(&delivery.last_error)
+        .bind(delivery.updated_time)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list(&self, status: DeliveryStatus, limit: i64) -> anyhow::Result<Vec<Delivery>> {
+        let rows = sqlx::query_as::<_, DeliveryRow>(
+            r#"
+SELECT *
+FROM crunch_webhook_deliveries
+WHERE status = $1
+ORDER BY updated_time ASC
+LIMIT $2;
+"#,
+        )
+        .bind(status.as_str())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Delivery::try_from).collect()
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use crunch_traits::{errors::TransportError, EventInfo, Transport};
+use futures::Stream;
+use tokio::sync::mpsc;
+
+use crate::signature;
+
+const CHANNEL_SIZE: usize = 100;
+
+type Subscriptions = HashMap<EventInfo, Vec<mpsc::Sender<Vec<u8>>>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("missing or invalid header: {0}")]
+    InvalidHeader(&'static str),
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("timestamp is outside of the tolerance")]
+    Expired,
+    #[error("already received: {0}")]
+    Replayed(String),
+    #[error("invalid envelope: {0}")]
+    InvalidEnvelope(#[source] crunch_envelope::EnvelopeError),
+    #[error("envelope doesn't match the event in the headers: {0}")]
+    EnvelopeMismatch(EventInfo),
+    #[error("no subscription for: {0}")]
+    NoSubscription(EventInfo),
+}
+
+impl WebhookError {
+    /// The status to answer the sender with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            WebhookError::InvalidSignature | WebhookError::Expired => StatusCode::UNAUTHORIZED,
+            // The sender treats it as delivered
+            WebhookError::Replayed(_) => StatusCode::CONFLICT,
+            WebhookError::NoSubscription(_) => StatusCode::NOT_FOUND,
+            WebhookError::InvalidHeader(_)
+            | WebhookError::InvalidEnvelope(_)
+            | WebhookError::EnvelopeMismatch(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// The receiving side of [`crate::WebhookTransport`], verifies the signature of posted events and hands them to its
+/// subscriptions.
+///
+/// It implements [`Transport`] such that it can be used as the transport of a `Subscriber`, and the handlers of a
+/// service work unchanged. Mount [`WebhookReceiver::router`] in a http server, or call [`WebhookReceiver::receive`] from
+/// one
+#[derive(Clone)]
+pub struct WebhookReceiver {
+    secret: Arc<Vec<u8>>,
+    tolerance: Duration,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    /// Timestamps of the ids received within the tolerance, older ids are rejected as expired instead
+    seen: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl WebhookReceiver {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: Arc::new(secret.into()),
+            tolerance: Duration::from_secs(5 * 60),
+            subscriptions: Arc::default(),
+            seen: Arc::default(),
+        }
+    }
+
+    /// How far the timestamp of an event may be from the clock of the receiver, defaults to 5 minutes
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Verifies the event and hands it to the subscriptions, returns once they have queued it. An id is only accepted
+    /// once, such that a captured request can't be replayed within the tolerance
+    pub async fn receive(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), WebhookError> {
+        let header = move |name: &'static str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .ok_or(WebhookError::InvalidHeader(name))
+        };
+
+        let id = header(signature::ID_HEADER)?;
+        let timestamp: u64 = header(signature::TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| WebhookError::InvalidHeader(signature::TIMESTAMP_HEADER))?;
+        let event_info = EventInfo {
+            domain: header(signature::DOMAIN_HEADER)?.to_string(),
+            entity_type: header(signature::ENTITY_HEADER)?.to_string(),
+            event_name: header(signature::EVENT_HEADER)?.to_string(),
+        };
+        if !signature::verify(
+            &self.secret,
+            id,
+            timestamp,
+            &event_info,
+            body,
+            header(signature::SIGNATURE_HEADER)?,
+        ) {
+            return Err(WebhookError::InvalidSignature);
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if now.abs_diff(timestamp) > self.tolerance.as_secs() {
+            return Err(WebhookError::Expired);
+        }
+
+        self.remember(id, timestamp, now)?;
+        let result = self.deliver(&event_info, body).await;
+        if result.is_err() {
+            // Such that the sender can retry it
+            self.seen.lock().unwrap().remove(id);
+        }
+        result?;
+
+        tracing::trace!("received webhook: {}", id);
+
+        Ok(())
+    }
+
+    fn remember(&self, id: &str, timestamp: u64, now: u64) -> Result<(), WebhookError> {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, timestamp| now.abs_diff(*timestamp) <= self.tolerance.as_secs());
+        if seen.contains_key(id) {
+            return Err(WebhookError::Replayed(id.to_string()));
+        }
+        seen.insert(id.to_string(), timestamp);
+
+        Ok(())
+    }
+
+    async fn deliver(&self, event_info: &EventInfo, body: &[u8]) -> Result<(), WebhookError> {
+        let (content, metadata) =
+            crunch_envelope::proto::unwrap(body).map_err(WebhookError::InvalidEnvelope)?;
+        if metadata.domain != event_info.domain || metadata.entity != event_info.entity_type {
+            return Err(WebhookError::EnvelopeMismatch(event_info.clone()));
+        }
+
+        let senders = {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            let senders = subscriptions.entry(event_info.clone()).or_default();
+            senders.retain(|s| !s.is_closed());
+            senders.clone()
+        };
+        if senders.is_empty() {
+            return Err(WebhookError::NoSubscription(event_info.clone()));
+        }
+
+        for sender in senders {
+            if sender.send(content.clone()).await.is_err() {
+                tracing::debug!("subscription closed for: {}", event_info);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A router accepting events posted to `/`
+    pub fn router(&self) -> axum::Router {
+        axum::Router::new()
+            .route("/", axum::routing::post(handle))
+            .with_state(self.clone())
+    }
+}
+
+async fn handle(
+    State(receiver): State<WebhookReceiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    match receiver.receive(&headers, &body).await {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(e) => {
+            tracing::warn!("rejected webhook: {}", e);
+            (e.status(), e.to_string()).into_response()
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for WebhookReceiver {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        _event_info: &EventInfo,
+        _content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        Err(TransportError::Err(anyhow::anyhow!(
+            "the webhook receiver can't publish, use the webhook transport instead"
+        )))
+    }
+
+    async fn subscriber(
+        &self,
+        event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        let (sender, receiver) = mpsc::channel(CHANNEL_SIZE);
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .entry(event_info.clone())
+            .or_default()
+            .push(sender);
+
+        Ok(Some(Box::pin(tokio_stream::wrappers::ReceiverStream::new(
+            receiver,
+        ))))
+    }
+}
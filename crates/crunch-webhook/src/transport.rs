@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use crunch_traits::{errors::TransportError, EventInfo, Transport};
+use futures::Stream;
+use uuid::Uuid;
+
+use crate::{
+    signature, Delivery, DeliveryStatus, DeliveryStore, DynDeliveryStore, InMemoryDeliveryStore,
+};
+
+#[derive(Clone)]
+pub struct WebhookTransportOptions {
+    /// Shared with the receivers, to sign the events with
+    pub secret: Vec<u8>,
+    /// Attempts per url before a delivery fails
+    pub max_attempts: u32,
+    /// Wait before the second attempt, doubled for every attempt after it up to `max_backoff`
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Timeout of a single request
+    pub timeout: Duration,
+    endpoints: HashMap<EventInfo, Vec<String>>,
+    deliveries: DynDeliveryStore,
+}
+
+impl WebhookTransportOptions {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            endpoints: HashMap::new(),
+            deliveries: Arc::new(InMemoryDeliveryStore::default()),
+        }
+    }
+
+    /// Delivers the event to `url`, an event may be delivered to any number of urls
+    pub fn with_endpoint(mut self, event_info: EventInfo, url: impl Into<String>) -> Self {
+        self.endpoints
+            .entry(event_info)
+            .or_default()
+            .push(url.into());
+        self
+    }
+
+    /// Where the status of deliveries is kept, defaults to an [`InMemoryDeliveryStore`]
+    pub fn with_delivery_store(
+        mut self,
+        deliveries: impl DeliveryStore + Send + Sync + 'static,
+    ) -> Self {
+        self.deliveries = Arc::new(deliveries);
+        self
+    }
+}
+
+/// A transport posting events to the urls registered for them, for consumers which can't reach the broker.
+///
+/// Events are sent in the crunch envelope, signed with the headers of [`signature`]. Publishing fails when a url
+/// couldn't be reached after every attempt, such that the outbox publishes the event again. Receivers may as such see
+/// an event more than once
+#[derive(Clone)]
+pub struct WebhookTransport {
+    client: reqwest::Client,
+    options: WebhookTransportOptions,
+}
+
+impl WebhookTransport {
+    pub fn new(options: WebhookTransportOptions) -> Result<Self, TransportError> {
+        let client = reqwest::Client::builder()
+            .timeout(options.timeout)
+            .build()
+            .map_err(|e| anyhow::anyhow!("failed to create http client: {}", e))
+            .map_err(TransportError::Err)?;
+
+        Ok(Self { client, options })
+    }
+
+    async fn deliver(&self, url: &str, event_info: &EventInfo, body: &[u8]) -> anyhow::Result<()> {
+        let mut delivery = Delivery {
+            id: Uuid::new_v4(),
+            url: url.to_string(),
+            event_info: event_info.clone(),
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            updated_time: chrono::Utc::now(),
+        };
+        self.options.deliveries.record(&delivery).await?;
+
+        let mut backoff = self.options.initial_backoff;
+        loop {
+            delivery.attempts += 1;
+            let result = self.send(&delivery, body).await;
+            delivery.updated_time = chrono::Utc::now();
+
+            match result {
+                Ok(()) => {
+                    delivery.status = DeliveryStatus::Delivered;
+                    self.options.deliveries.record(&delivery).await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "failed to deliver: {} to: {} (attempt {}): {}",
+                        delivery.id,
+                        url,
+                        delivery.attempts,
+                        e
+                    );
+                    delivery.last_error = Some(e.to_string());
+
+                    if delivery.attempts >= self.options.max_attempts {
+                        delivery.status = DeliveryStatus::Failed;
+                        self.options.deliveries.record(&delivery).await?;
+                        anyhow::bail!(
+                            "failed to deliver: {} to: {} after {} attempts: {}",
+                            delivery.id,
+                            url,
+                            delivery.attempts,
+                            e
+                        );
+                    }
+                    self.options.deliveries.record(&delivery).await?;
+
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.options.max_backoff);
+                }
+            }
+        }
+    }
+
+    async fn send(&self, delivery: &Delivery, body: &[u8]) -> anyhow::Result<()> {
+        let id = delivery.id.to_string();
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        let response = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .header(signature::ID_HEADER, &id)
+            .header(signature::TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                signature::SIGNATURE_HEADER,
+                signature::sign(
+                    &self.options.secret,
+                    &id,
+                    timestamp,
+                    &delivery.event_info,
+                    body,
+                ),
+            )
+            .header(signature::DOMAIN_HEADER, &delivery.event_info.domain)
+            .header(signature::ENTITY_HEADER, &delivery.event_info.entity_type)
+            .header(signature::EVENT_HEADER, &delivery.event_info.event_name)
+            .body(body.to_vec())
+            .send()
+            .await?;
+        // The receiver already has the delivery, from an attempt whose answer was lost
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(());
+        }
+        response.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for WebhookTransport {
+    type Stream = Pin<Box<dyn Stream<Item = Vec<u8>> + Send>>;
+
+    async fn publish(
+        &self,
+        event_info: &EventInfo,
+        content: Vec<u8>,
+    ) -> Result<(), TransportError> {
+        let Some(urls) = self.options.endpoints.get(event_info) else {
+            tracing::debug!("no webhooks registered for: {}", event_info);
+            return Ok(());
+        };
+
+        let body =
+            crunch_envelope::proto::wrap(&event_info.domain, &event_info.entity_type, &content);
+        let errors: Vec<_> =
+            futures::future::join_all(urls.iter().map(|url| self.deliver(url, event_info, &body)))
+                .await
+                .into_iter()
+                .filter_map(|result| result.err())
+                .map(|e| e.to_string())
+                .collect();
+
+        if !errors.is_empty() {
+            return Err(TransportError::Err(anyhow::anyhow!(
+                "failed webhook deliveries: {}",
+                errors.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Webhooks only send events, see [`crate::WebhookReceiver`] for the receiving side
+    async fn subscriber(
+        &self,
+        _event_info: &EventInfo,
+    ) -> Result<Option<Self::Stream>, TransportError> {
+        Ok(None)
+    }
+}
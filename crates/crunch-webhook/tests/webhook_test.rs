@@ -0,0 +1,208 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+};
+use crunch_traits::{EventInfo, Transport};
+use crunch_webhook::{
+    signature, DeliveryStatus, DeliveryStore, InMemoryDeliveryStore, WebhookError, WebhookReceiver,
+    WebhookTransport, WebhookTransportOptions,
+};
+use futures::StreamExt;
+
+async fn serve(router: axum::Router) -> anyhow::Result<String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    Ok(format!("http://{}/", addr))
+}
+
+fn event_info() -> EventInfo {
+    EventInfo {
+        domain: "some-domain".into(),
+        entity_type: "some-entity-type".into(),
+        event_name: "some-event".into(),
+    }
+}
+
+fn options(secret: &str, url: &str, store: &InMemoryDeliveryStore) -> WebhookTransportOptions {
+    let mut options = WebhookTransportOptions::new(secret)
+        .with_endpoint(event_info(), url)
+        .with_delivery_store(store.clone());
+    options.max_attempts = 3;
+    options.initial_backoff = Duration::from_millis(10);
+    options
+}
+
+#[tokio::test]
+async fn test_webhook_delivers_to_receiver() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let mut stream = receiver.subscriber(&event_info()).await?.unwrap();
+    let url = serve(receiver.router()).await?;
+
+    let store = InMemoryDeliveryStore::default();
+    let transport = WebhookTransport::new(options("secret", &url, &store))?;
+    transport
+        .publish(&event_info(), b"content".to_vec())
+        .await?;
+
+    assert_eq!(
+        Some(b"content".to_vec()),
+        tokio::time::timeout(Duration::from_secs(5), stream.next()).await?
+    );
+    let delivered = store.list(DeliveryStatus::Delivered, 10).await?;
+    assert_eq!(1, delivered.len());
+    assert_eq!(1, delivered[0].attempts);
+    assert_eq!(url, delivered[0].url);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_webhook_retries_until_delivered() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let mut stream = receiver.subscriber(&event_info()).await?.unwrap();
+
+    // Fails the first two requests, then hands them to the receiver
+    let requests = Arc::new(AtomicU32::new(0));
+    let flaky = axum::Router::new().route(
+        "/",
+        axum::routing::post({
+            let requests = requests.clone();
+            let receiver = receiver.clone();
+            move |headers: HeaderMap, body: Bytes| async move {
+                if requests.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                match receiver.receive(&headers, &body).await {
+                    Ok(()) => StatusCode::ACCEPTED,
+                    Err(e) => e.status(),
+                }
+            }
+        }),
+    );
+    let url = serve(flaky).await?;
+
+    let store = InMemoryDeliveryStore::default();
+    let transport = WebhookTransport::new(options("secret", &url, &store))?;
+    transport
+        .publish(&event_info(), b"content".to_vec())
+        .await?;
+
+    assert_eq!(
+        Some(b"content".to_vec()),
+        tokio::time::timeout(Duration::from_secs(5), stream.next()).await?
+    );
+    let delivered = store.list(DeliveryStatus::Delivered, 10).await?;
+    assert_eq!(1, delivered.len());
+    assert_eq!(3, delivered[0].attempts);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_webhook_fails_with_wrong_secret() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let _stream = receiver.subscriber(&event_info()).await?.unwrap();
+    let url = serve(receiver.router()).await?;
+
+    let store = InMemoryDeliveryStore::default();
+    let transport = WebhookTransport::new(options("wrong-secret", &url, &store))?;
+    assert!(transport
+        .publish(&event_info(), b"content".to_vec())
+        .await
+        .is_err());
+
+    let failed = store.list(DeliveryStatus::Failed, 10).await?;
+    assert_eq!(1, failed.len());
+    assert_eq!(3, failed[0].attempts);
+    assert!(failed[0]
+        .last_error
+        .as_ref()
+        .is_some_and(|e| e.contains("401")));
+
+    Ok(())
+}
+
+fn signed_headers(id: &str, timestamp: u64, body: &[u8]) -> anyhow::Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    headers.insert(signature::ID_HEADER, id.parse()?);
+    headers.insert(signature::TIMESTAMP_HEADER, timestamp.to_string().parse()?);
+    headers.insert(
+        signature::SIGNATURE_HEADER,
+        signature::sign(b"secret", id, timestamp, &event_info(), body).parse()?,
+    );
+    headers.insert(signature::DOMAIN_HEADER, "some-domain".parse()?);
+    headers.insert(signature::ENTITY_HEADER, "some-entity-type".parse()?);
+    headers.insert(signature::EVENT_HEADER, "some-event".parse()?);
+
+    Ok(headers)
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
+#[tokio::test]
+async fn test_receiver_rejects_expired_timestamp() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let _stream = receiver.subscriber(&event_info()).await?.unwrap();
+
+    let body = crunch_envelope::proto::wrap("some-domain", "some-entity-type", b"content");
+    let headers = signed_headers("some-id", 1_000_000, &body)?;
+
+    assert!(matches!(
+        receiver.receive(&headers, &body).await,
+        Err(WebhookError::Expired)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receiver_rejects_replayed_id() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let _stream = receiver.subscriber(&event_info()).await?.unwrap();
+
+    let body = crunch_envelope::proto::wrap("some-domain", "some-entity-type", b"content");
+    let headers = signed_headers("some-id", now()?, &body)?;
+
+    receiver.receive(&headers, &body).await?;
+    assert!(matches!(
+        receiver.receive(&headers, &body).await,
+        Err(WebhookError::Replayed(id)) if id == "some-id"
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_receiver_rejects_changed_event() -> anyhow::Result<()> {
+    let receiver = WebhookReceiver::new("secret");
+    let other = EventInfo {
+        event_name: "other-event".into(),
+        ..event_info()
+    };
+    let _stream = receiver.subscriber(&other).await?.unwrap();
+
+    let body = crunch_envelope::proto::wrap("some-domain", "some-entity-type", b"content");
+    let mut headers = signed_headers("some-id", now()?, &body)?;
+    headers.insert(signature::EVENT_HEADER, "other-event".parse()?);
+
+    assert!(matches!(
+        receiver.receive(&headers, &body).await,
+        Err(WebhookError::InvalidSignature)
+    ));
+
+    Ok(())
+}
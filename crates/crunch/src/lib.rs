@@ -6,7 +6,9 @@ mod transport;
 
 #[cfg(feature = "traits")]
 pub mod traits {
-    pub use crunch_traits::{Deserializer, Event, EventInfo, Persistence, Serializer, Transport};
+    pub use crunch_traits::{
+        Deserializer, Event, EventInfo, PartitionKey, Persistence, Serializer, Transport,
+    };
 }
 
 pub mod errors {
@@ -25,6 +27,51 @@ pub mod nats {
     pub use crunch_nats::{NatsConnectCredentials, NatsConnectOptions};
 }
 
+#[cfg(feature = "postgres")]
+pub mod postgres {
+    pub use crunch_postgres::{PostgresPersistence, PostgresTransport, PostgresTransportOptions};
+    pub use sqlx::PgPool;
+}
+
+#[cfg(feature = "redis")]
+pub mod redis {
+    pub use crunch_redis::{RedisTransport, RedisTransportOptions};
+}
+
+#[cfg(feature = "amqp")]
+pub mod amqp {
+    pub use crunch_amqp::{AmqpTransport, AmqpTransportOptions};
+}
+
+#[cfg(feature = "kafka")]
+pub mod kafka {
+    pub use crunch_kafka::{KafkaTransport, KafkaTransportOptions, TopicLayout};
+}
+
+#[cfg(feature = "webhook")]
+pub mod webhook {
+    pub use crunch_webhook::{
+        signature, Delivery, DeliveryStatus, DeliveryStore, InMemoryDeliveryStore, WebhookError,
+        WebhookReceiver, WebhookTransport, WebhookTransportOptions,
+    };
+
+    #[cfg(feature = "webhook-postgres")]
+    pub use crunch_webhook::PostgresDeliveryStore;
+}
+
+#[cfg(feature = "embedded")]
+pub mod embedded {
+    pub use crunch_embedded::{EmbeddedOptions, SyncPolicy};
+}
+
+/// Includes the events generated by `crunch_codegen::Builder` in a `build.rs`, with a module per `output-path`
+#[macro_export]
+macro_rules! include_events {
+    () => {
+        include!(concat!(env!("OUT_DIR"), "/crunch/mod.rs"));
+    };
+}
+
 #[derive(Clone)]
 pub struct Crunch {
     publisher: Publisher,
@@ -98,6 +145,116 @@ mod builder {
             Ok(self)
         }
 
+        #[cfg(feature = "sqlite")]
+        pub async fn with_sqlite_persistence(
+            &mut self,
+            path: impl AsRef<std::path::Path>,
+        ) -> Result<&mut Self, crunch_traits::errors::PersistenceError> {
+            self.persistence = Some(Persistence::sqlite(path).await?);
+            Ok(self)
+        }
+
+        #[cfg(feature = "postgres")]
+        pub async fn with_postgres_persistence(
+            &mut self,
+            dsn: &str,
+        ) -> Result<&mut Self, crunch_traits::errors::PersistenceError> {
+            self.persistence = Some(Persistence::postgres(dsn).await?);
+            Ok(self)
+        }
+
+        /// Shares the pool of the application with the outbox
+        #[cfg(feature = "postgres")]
+        pub async fn with_postgres_pool(
+            &mut self,
+            pool: crate::postgres::PgPool,
+        ) -> Result<&mut Self, crunch_traits::errors::PersistenceError> {
+            self.persistence = Some(Persistence::postgres_pool(pool).await?);
+            Ok(self)
+        }
+
+        /// Uses postgres as the transport as well, such that crunch needs nothing but the database
+        #[cfg(feature = "postgres")]
+        pub async fn with_postgres_transport(
+            &mut self,
+            pool: crate::postgres::PgPool,
+            options: crate::postgres::PostgresTransportOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::TransportError> {
+            self.transport = Some(Transport::postgres(pool, options).await?);
+            Ok(self)
+        }
+
+        #[cfg(feature = "redis")]
+        pub async fn with_redis_transport(
+            &mut self,
+            url: &str,
+            options: crate::redis::RedisTransportOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::TransportError> {
+            self.transport = Some(Transport::redis(url, options).await?);
+            Ok(self)
+        }
+
+        #[cfg(feature = "amqp")]
+        pub async fn with_amqp_transport(
+            &mut self,
+            uri: &str,
+            options: crate::amqp::AmqpTransportOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::TransportError> {
+            self.transport = Some(Transport::amqp(uri, options).await?);
+            Ok(self)
+        }
+
+        /// Uses kafka as the transport, register events with a [`crate::traits::PartitionKey`] on the options to keep
+        /// their order per key
+        #[cfg(feature = "kafka")]
+        pub fn with_kafka_transport(
+            &mut self,
+            options: crate::kafka::KafkaTransportOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::TransportError> {
+            self.transport = Some(Transport::kafka(options)?);
+            Ok(self)
+        }
+
+        /// Posts events to the urls registered on the options, for consumers outside of the network
+        #[cfg(feature = "webhook")]
+        pub fn with_webhook_transport(
+            &mut self,
+            options: crate::webhook::WebhookTransportOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::TransportError> {
+            self.transport = Some(Transport::webhook(options)?);
+            Ok(self)
+        }
+
+        /// Subscribes to events posted to the receiver, serve its router to accept them
+        #[cfg(feature = "webhook")]
+        pub fn with_webhook_receiver(
+            &mut self,
+            receiver: crate::webhook::WebhookReceiver,
+        ) -> &mut Self {
+            self.transport = Some(Transport::webhook_receiver(receiver));
+            self
+        }
+
+        pub fn with_transport(&mut self, transport: impl Into<Transport>) -> &mut Self {
+            self.transport = Some(transport.into());
+            self
+        }
+
+        pub fn with_persistence(&mut self, persistence: impl Into<Persistence>) -> &mut Self {
+            self.persistence = Some(persistence.into());
+            self
+        }
+
+        #[cfg(feature = "embedded")]
+        pub async fn with_embedded_persistence(
+            &mut self,
+            dir: impl AsRef<std::path::Path>,
+            options: crate::embedded::EmbeddedOptions,
+        ) -> Result<&mut Self, crunch_traits::errors::PersistenceError> {
+            self.persistence = Some(Persistence::embedded(dir, options).await?);
+            Ok(self)
+        }
+
         pub fn with_outbox(&mut self, enabled: bool) -> &mut Self {
             self.outbox_enabled = enabled;
             self
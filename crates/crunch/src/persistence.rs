@@ -6,16 +6,68 @@ pub struct Persistence {
 }
 
 impl Persistence {
+    /// Plugs in any backend implementing the persistence trait
+    pub fn new(inner: Arc<dyn crunch_traits::Persistence + Send + Sync + 'static>) -> Self {
+        Self { inner }
+    }
+
     #[cfg(feature = "in-memory")]
     pub fn in_memory() -> Self {
         use crunch_in_memory::persistence::InMemoryPersistence;
 
-        Self {
-            inner: std::sync::Arc::new(InMemoryPersistence {
-                outbox: std::sync::Arc::default(),
-                store: std::sync::Arc::default(),
-            }),
-        }
+        Self::new(Arc::new(InMemoryPersistence {
+            outbox: Arc::default(),
+            store: Arc::default(),
+        }))
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub async fn sqlite(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, crunch_traits::errors::PersistenceError> {
+        let persistence = crunch_sqlite::SqlitePersistence::new(path)
+            .await
+            .map_err(crunch_traits::errors::PersistenceError::AnyErr)?;
+
+        Ok(Self::new(Arc::new(persistence)))
+    }
+
+    #[cfg(feature = "postgres")]
+    pub async fn postgres(dsn: &str) -> Result<Self, crunch_traits::errors::PersistenceError> {
+        let persistence = crunch_postgres::PostgresPersistence::new(dsn)
+            .await
+            .map_err(crunch_traits::errors::PersistenceError::AnyErr)?;
+
+        Ok(Self::new(Arc::new(persistence)))
+    }
+
+    #[cfg(feature = "postgres")]
+    pub async fn postgres_pool(
+        pool: sqlx::PgPool,
+    ) -> Result<Self, crunch_traits::errors::PersistenceError> {
+        let persistence = crunch_postgres::PostgresPersistence::new_from_pool(pool)
+            .await
+            .map_err(crunch_traits::errors::PersistenceError::AnyErr)?;
+
+        Ok(Self::new(Arc::new(persistence)))
+    }
+
+    #[cfg(feature = "embedded")]
+    pub async fn embedded(
+        dir: impl AsRef<std::path::Path>,
+        options: crate::embedded::EmbeddedOptions,
+    ) -> Result<Self, crunch_traits::errors::PersistenceError> {
+        let persistence = crunch_embedded::EmbeddedPersistence::open(dir, options)
+            .await
+            .map_err(crunch_traits::errors::PersistenceError::AnyErr)?;
+
+        Ok(Self::new(Arc::new(persistence)))
+    }
+}
+
+impl From<Arc<dyn crunch_traits::Persistence + Send + Sync + 'static>> for Persistence {
+    fn from(value: Arc<dyn crunch_traits::Persistence + Send + Sync + 'static>) -> Self {
+        Self::new(value)
     }
 }
 
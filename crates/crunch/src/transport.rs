@@ -24,6 +24,62 @@ impl Transport {
         )))
     }
 
+    #[cfg(feature = "postgres")]
+    pub async fn postgres(
+        pool: sqlx::PgPool,
+        options: crate::postgres::PostgresTransportOptions,
+    ) -> Result<Self, crunch_traits::errors::TransportError> {
+        Ok(Self(std::sync::Arc::new(
+            crunch_postgres::PostgresTransport::new(pool, options)
+                .await
+                .map_err(crunch_traits::errors::TransportError::Err)?,
+        )))
+    }
+
+    #[cfg(feature = "redis")]
+    pub async fn redis(
+        url: &str,
+        options: crate::redis::RedisTransportOptions,
+    ) -> Result<Self, crunch_traits::errors::TransportError> {
+        Ok(Self(std::sync::Arc::new(
+            crunch_redis::RedisTransport::new(url, options).await?,
+        )))
+    }
+
+    #[cfg(feature = "amqp")]
+    pub async fn amqp(
+        uri: &str,
+        options: crate::amqp::AmqpTransportOptions,
+    ) -> Result<Self, crunch_traits::errors::TransportError> {
+        Ok(Self(std::sync::Arc::new(
+            crunch_amqp::AmqpTransport::new(uri, options).await?,
+        )))
+    }
+
+    #[cfg(feature = "kafka")]
+    pub fn kafka(
+        options: crate::kafka::KafkaTransportOptions,
+    ) -> Result<Self, crunch_traits::errors::TransportError> {
+        Ok(Self(std::sync::Arc::new(
+            crunch_kafka::KafkaTransport::new(options)?,
+        )))
+    }
+
+    #[cfg(feature = "webhook")]
+    pub fn webhook(
+        options: crate::webhook::WebhookTransportOptions,
+    ) -> Result<Self, crunch_traits::errors::TransportError> {
+        Ok(Self(std::sync::Arc::new(
+            crunch_webhook::WebhookTransport::new(options)?,
+        )))
+    }
+
+    /// Subscribes to the events posted to the receiver
+    #[cfg(feature = "webhook")]
+    pub fn webhook_receiver(receiver: crate::webhook::WebhookReceiver) -> Self {
+        Self(std::sync::Arc::new(receiver))
+    }
+
     #[cfg(feature = "nodata")]
     pub fn nodata(host: &str) -> Result<Self, crunch_traits::errors::TransportError> {
         Ok(Self(std::sync::Arc::new(
@@ -1,4 +1,4 @@
-#[allow(clippy::derive_partial_eq_without_eq)]
+// This file is @generated by prost-build.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MyInclude {
     #[prost(string, tag = "1")]
@@ -1,3 +1,4 @@
+#[allow(dead_code)]
 mod gencrunch;
 
 use gencrunch::basic::{includes::my_include::MyInclude, my_event::MyEvent};
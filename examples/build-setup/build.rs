@@ -0,0 +1,3 @@
+fn main() -> anyhow::Result<()> {
+    crunch_codegen::Builder::new().build()
+}
@@ -0,0 +1,27 @@
+crunch::include_events!();
+
+use gencrunch::examples::user::UserCreated;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let crunch = crunch::Builder::default().build()?;
+
+    crunch
+        .subscribe(|item: UserCreated| async move {
+            println!("received item: {:?}", item);
+
+            Ok(())
+        })
+        .await?;
+
+    crunch
+        .publish(UserCreated {
+            user_id: "some-user".into(),
+        })
+        .await?;
+
+    // Sleep a while to let subscriber catch item
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+    Ok(())
+}